@@ -1,16 +1,86 @@
 use {
-    crate::{request, Meta, KAKAO_LOCAL_API_BASE_URL},
+    crate::{
+        paginate::{paginate, Page},
+        request, Documents, Error, KakaoClient, Meta,
+    },
+    futures::{Stream, TryFutureExt},
     serde::Deserialize,
+    std::{borrow::Cow, fmt},
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Address {
     pub address: Option<String>,
+    pub address_type: Option<AddressType>,
+    /// Coordinate of the matched document itself, which region-only matches also have.
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
     pub land_lot: Option<LandLotAddress>,
     pub road: Option<RoadAddress>,
 }
 
+impl Address {
+    /// Coordinate of the road address, the land-lot address or the document, whichever is found
+    /// first, as `(longitude, latitude)`.
+    pub fn best_coordinate(&self) -> Option<(f64, f64)> {
+        let road = self
+            .road
+            .as_ref()
+            .map(|addr| (addr.longitude, addr.latitude));
+        let land_lot = self
+            .land_lot
+            .as_ref()
+            .map(|addr| (addr.longitude, addr.latitude));
+        road.into_iter()
+            .chain(land_lot)
+            .chain(Some((self.longitude, self.latitude)))
+            .find_map(|coord| match coord {
+                (Some(longitude), Some(latitude)) => Some((longitude, latitude)),
+                _ => None,
+            })
+    }
+}
+
+/// What the query matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum AddressType {
+    /// A region name, e.g. a town.
+    Region,
+    /// A road name.
+    Road,
+    /// A land-lot address.
+    RegionAddr,
+    /// A road address.
+    RoadAddr,
+}
+
+impl AddressType {
+    pub fn to_code<'a>(self) -> &'a str {
+        match self {
+            AddressType::Region => "REGION",
+            AddressType::Road => "ROAD",
+            AddressType::RegionAddr => "REGION_ADDR",
+            AddressType::RoadAddr => "ROAD_ADDR",
+        }
+    }
+}
+
+/// How the query is matched against addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AnalyzeType {
+    /// Fuzzy matching, the default of Kakao.
+    Similar,
+    /// Only addresses matching the query exactly.
+    Exact,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LandLotAddress {
     pub address: String,
     pub province: String,
@@ -23,11 +93,12 @@ pub struct LandLotAddress {
     pub main_address_number: Option<usize>,
     pub sub_address_number: Option<usize>,
     pub zip_code: Option<usize>,
-    pub longitude: Option<f32>,
-    pub latitude: Option<f32>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoadAddress {
     pub address: String,
     pub province: String,
@@ -39,11 +110,12 @@ pub struct RoadAddress {
     pub sub_building_number: Option<usize>,
     pub building_name: String,
     pub post_code: Option<usize>,
-    pub longitude: Option<f32>,
-    pub latitude: Option<f32>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddressResponse {
     pub addresses: Vec<Address>,
     pub total_count: usize,
@@ -52,27 +124,43 @@ pub struct AddressResponse {
 }
 
 #[derive(Debug, Clone)]
-pub struct AddressRequest {
-    base_url: String,
-    app_key: String,
+pub struct AddressRequest<'a> {
+    client: Cow<'a, KakaoClient>,
     query: String,
     page: usize,
     size: usize,
+    prefetch: usize,
+    analyze_type: Option<AnalyzeType>,
 }
 
-impl AddressRequest {
+impl AddressRequest<'static> {
     pub fn new(app_key: &str, query: &str) -> Self {
+        AddressRequest::with_client(Cow::Owned(KakaoClient::new(app_key)), query)
+    }
+}
+
+impl<'a> AddressRequest<'a> {
+    pub(crate) fn with_client(client: Cow<'a, KakaoClient>, query: &str) -> Self {
+        let size = client.size;
         AddressRequest {
-            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
-            app_key: app_key.to_string(),
+            client,
             query: query.to_string(),
             page: 1,
-            size: 15,
+            size,
+            prefetch: 0,
+            analyze_type: None,
         }
     }
 
     pub fn base_url(&mut self, base_url: &str) -> &mut Self {
-        self.base_url = base_url.to_string();
+        self.client.to_mut().base_url = base_url.to_string();
+        self
+    }
+
+    /// Sends the request even if the client's cache holds a response, and leaves the cache as
+    /// is.
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.client.to_mut().cache = None;
         self
     }
 
@@ -86,29 +174,57 @@ impl AddressRequest {
         self
     }
 
-    pub async fn get(&self) -> Result<AddressResponse, failure::Error> {
-        static API_PATH: &'static str = "/search/address.json";
-
-        let resp = request::<RawResponse>(
-            &self.base_url,
-            API_PATH,
-            &[
-                ("query", self.query.clone()),
-                ("page", self.page.to_string()),
-                ("size", self.size.to_string()),
-            ],
-            &self.app_key,
-        )
-        .await?;
+    /// Requests following pages concurrently while the stream is consumed. At most `pages` pages
+    /// beyond the current one are in flight; `0` (the default) fetches one page at a time.
+    pub fn prefetch(&mut self, pages: usize) -> &mut Self {
+        self.prefetch = pages;
+        self
+    }
+
+    pub fn analyze_type(&mut self, analyze_type: AnalyzeType) -> &mut Self {
+        self.analyze_type = Some(analyze_type);
+        self
+    }
+
+    pub async fn get(&self) -> Result<AddressResponse, Error> {
+        self.get_page(self.page).await
+    }
+
+    /// Streams the addresses of every page, starting from the page set with
+    /// [`page`](Self::page).
+    pub fn stream(&self) -> impl Stream<Item = Result<Address, Error>> + '_ {
+        paginate(self.page, self.size, self.prefetch, move |page| {
+            self.get_page(page).map_ok(Into::into)
+        })
+    }
+
+    async fn get_page(&self, page: usize) -> Result<AddressResponse, Error> {
+        static API_PATH: &str = "/search/address.json";
+
+        let mut params = vec![
+            ("query", self.query.clone()),
+            ("page", page.to_string()),
+            ("size", self.size.to_string()),
+        ];
+        if let Some(analyze_type) = self.analyze_type {
+            params.push(("analyze_type", analyze_type.to_string()));
+        }
+
+        let resp = request::<RawResponse>(&self.client, API_PATH, &params).await?;
         let addresses = resp
             .documents
             .into_iter()
             .map(|document| Address {
                 address: document.address_name,
+                address_type: document
+                    .address_type
+                    .as_deref()
+                    .and_then(parse_address_type),
+                longitude: document.x.parse::<f64>().ok(),
+                latitude: document.y.parse::<f64>().ok(),
                 land_lot: document.address.map(Into::into),
                 road: document.road_address.map(Into::into),
             })
-            .filter(|addr| addr.land_lot.is_some() || addr.road.is_some())
             .collect();
 
         Ok(AddressResponse {
@@ -120,15 +236,59 @@ impl AddressRequest {
     }
 }
 
+impl fmt::Display for AnalyzeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalyzeType::Similar => f.write_str("similar"),
+            AnalyzeType::Exact => f.write_str("exact"),
+        }
+    }
+}
+
+fn parse_address_type(s: &str) -> Option<AddressType> {
+    match s {
+        "REGION" => Some(AddressType::Region),
+        "ROAD" => Some(AddressType::Road),
+        "REGION_ADDR" => Some(AddressType::RegionAddr),
+        "ROAD_ADDR" => Some(AddressType::RoadAddr),
+        _ => None,
+    }
+}
+
+impl From<AddressResponse> for Page<Address> {
+    fn from(resp: AddressResponse) -> Self {
+        Page {
+            items: resp.addresses,
+            pageable_count: resp.pageable_count,
+            is_end: resp.is_end,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RawResponse {
     documents: Vec<Document>,
     meta: Meta,
 }
 
+impl Documents for RawResponse {
+    fn document_count(&self) -> Option<usize> {
+        Some(self.documents.len())
+    }
+
+    fn total_count(&self) -> Option<usize> {
+        Some(self.meta.total_count)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct Document {
     address_name: Option<String>,
+    address_type: Option<String>,
+    #[serde(default)]
+    x: String,
+    #[serde(default)]
+    y: String,
     address: Option<RawLandLotAddress>,
     road_address: Option<RawRoadAddress>,
 }
@@ -188,8 +348,8 @@ impl From<RawLandLotAddress> for LandLotAddress {
             main_address_number: raddr.main_address_no.parse::<usize>().ok(),
             sub_address_number: raddr.sub_address_no.parse::<usize>().ok(),
             zip_code: raddr.zip_code.parse::<usize>().ok(),
-            longitude: raddr.x.parse::<f32>().ok(),
-            latitude: raddr.y.parse::<f32>().ok(),
+            longitude: raddr.x.parse::<f64>().ok(),
+            latitude: raddr.y.parse::<f64>().ok(),
         }
     }
 }
@@ -207,8 +367,72 @@ impl From<RawRoadAddress> for RoadAddress {
             sub_building_number: raddr.sub_building_no.parse::<usize>().ok(),
             building_name: raddr.building_name,
             post_code: raddr.zone_no.parse::<usize>().ok(),
-            longitude: raddr.x.parse::<f32>().ok(),
-            latitude: raddr.y.parse::<f32>().ok(),
+            longitude: raddr.x.parse::<f64>().ok(),
+            latitude: raddr.y.parse::<f64>().ok(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{transport::MemoryTransport, KakaoClient};
+
+    #[tokio::test]
+    async fn test_parse_land_lot_address() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            "/search/address.json",
+            r#"{
+  "meta": { "total_count": 1, "pageable_count": 1, "is_end": true },
+  "documents": [
+    {
+      "address_name": "강원 평창군 대관령면 횡계리 산 1-1",
+      "y": "37.6802960744718",
+      "x": "128.71899468655",
+      "address_type": "REGION_ADDR",
+      "address": {
+        "address_name": "강원 평창군 대관령면 횡계리 산 1-1",
+        "region_1depth_name": "강원",
+        "region_2depth_name": "평창군",
+        "region_3depth_name": "대관령면 횡계리",
+        "region_3depth_h_name": "",
+        "h_code": "4276038000",
+        "b_code": "4276038021",
+        "mountain_yn": "Y",
+        "main_address_no": "1",
+        "sub_address_no": "1",
+        "zip_code": "",
+        "x": "128.71899468655",
+        "y": "37.6802960744718"
+      },
+      "road_address": null
+    }
+  ]
+}"#,
+        );
+        let client = KakaoClient::builder("key")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let resp = client.address("횡계리 산 1-1").get().await.unwrap();
+        let land_lot = resp.addresses[0].land_lot.as_ref().unwrap();
+        assert_eq!(land_lot.town, "대관령면 횡계리");
+        assert_eq!(land_lot.neighborhood, None);
+        assert_eq!(land_lot.h_code, Some(4276038000));
+        assert_eq!(land_lot.b_code, Some(4276038021));
+        assert_eq!(land_lot.is_mountain, Some(true));
+        assert_eq!(land_lot.main_address_number, Some(1));
+        assert_eq!(land_lot.sub_address_number, Some(1));
+        assert_eq!(land_lot.zip_code, None);
+        assert_eq!(land_lot.longitude, Some(128.71899468655));
+        assert!(resp.addresses[0].road.is_none());
+
+        let client = KakaoClient::builder("key")
+            .transport(MemoryTransport::new())
+            .build()
+            .unwrap();
+        let err = client.address("횡계리").get().await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::NOT_FOUND));
+    }
+}
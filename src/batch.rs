@@ -0,0 +1,355 @@
+//! Bulk geocoding of CSV files.
+//!
+//! A [`Batch`] reads the rows of a CSV file, geocodes one column with an address search, falls
+//! back to a keyword search when the address search finds nothing, and writes the input columns
+//! followed by the match:
+//!
+//! | column             | value                                                   |
+//! |--------------------|---------------------------------------------------------|
+//! | `road_address`     | matched road address                                    |
+//! | `land_lot_address` | matched land-lot address                                |
+//! | `longitude`        | see [`Address::best_coordinate`]                        |
+//! | `latitude`         |                                                         |
+//! | `b_code`           | legal region code of the land-lot address               |
+//! | `match_type`       | `address_type` of the match, `KEYWORD` for the fallback |
+//! | `confidence`       | see [`Match::confidence`]                               |
+//!
+//! Progress is checkpointed to a sidecar file next to the output, `<output>.checkpoint`. When a
+//! run stops, because of an error, a used up quota or a crash, running it again with the same
+//! files resumes after the last checkpointed row; rows written after the checkpoint are discarded
+//! and requested again. The sidecar is removed once every row is written.
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! let client = daummap::KakaoClient::new(APP_KEY);
+//! let stats = daummap::batch::Batch::new(&client, "address")
+//!     .concurrency(8)
+//!     .run("addresses.csv", "geocoded.csv")
+//!     .await
+//!     .unwrap();
+//! println!("{:?}", stats);
+//! # }
+//! ```
+//!
+//! Coordinates are reverse geocoded in bulk with [`reverse::ReverseBatch`].
+
+pub mod reverse;
+
+use {
+    crate::{Address, AddressType, Error, KakaoClient},
+    futures::stream::{self, StreamExt},
+    serde::{Deserialize, Serialize},
+    std::{
+        fs::{self, File, OpenOptions},
+        io::{self, Seek},
+        path::{Path, PathBuf},
+    },
+    thiserror::Error as ThisError,
+};
+
+const OUTPUT_COLUMNS: &[&str] = &[
+    "road_address",
+    "land_lot_address",
+    "longitude",
+    "latitude",
+    "b_code",
+    "match_type",
+    "confidence",
+];
+
+#[derive(Debug, ThisError)]
+pub enum BatchError {
+    #[error("Request failed: {0}")]
+    Api(#[from] Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Cannot read or write CSV: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Cannot read or write the checkpoint: {0}")]
+    Checkpoint(#[from] serde_json::Error),
+    #[error("The output is shorter than its checkpoint")]
+    CheckpointMismatch,
+    #[error("No column named {0}")]
+    MissingColumn(String),
+    #[error("Invalid point at line {0}")]
+    InvalidPoint(usize),
+    #[error("Cannot parse the input at line {line}: {source}")]
+    InvalidInput {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("The GeoJSON input has no features")]
+    MissingFeatures,
+}
+
+#[derive(Debug, Clone)]
+pub struct Batch<'a> {
+    client: &'a KakaoClient,
+    column: String,
+    concurrency: usize,
+    keyword_fallback: bool,
+    checkpoint_interval: usize,
+}
+
+/// Geocoding result of a query.
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub road_address: Option<String>,
+    pub land_lot_address: Option<String>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub b_code: Option<usize>,
+    /// `None` for keyword matches.
+    pub address_type: Option<AddressType>,
+    pub from_keyword: bool,
+    /// Between 0 and 1. An address match with a road or land-lot address scores 1, a match of a
+    /// region or a road name alone 0.5 and a keyword match 0.3. Scores are divided by the number
+    /// of results, as an ambiguous query may have picked the wrong one.
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BatchStats {
+    /// Rows written by this run.
+    pub rows: usize,
+    /// Rows skipped because an earlier run had written them.
+    pub resumed: usize,
+    pub address_matches: usize,
+    pub keyword_matches: usize,
+    pub unmatched: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    /// Number of input rows written.
+    rows: usize,
+    /// Length of the output at that point.
+    offset: u64,
+}
+
+impl<'a> Batch<'a> {
+    /// Geocodes the column named `column`.
+    pub fn new(client: &'a KakaoClient, column: &str) -> Self {
+        Batch {
+            client,
+            column: column.to_string(),
+            concurrency: 4,
+            keyword_fallback: true,
+            checkpoint_interval: 100,
+        }
+    }
+
+    /// Number of rows geocoded at once. Defaults to 4.
+    pub fn concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Whether to search by keyword when the address search finds nothing. Defaults to `true`.
+    pub fn keyword_fallback(&mut self, keyword_fallback: bool) -> &mut Self {
+        self.keyword_fallback = keyword_fallback;
+        self
+    }
+
+    /// Number of rows between checkpoints, which bounds the rows requested again after a crash.
+    /// Defaults to 100.
+    pub fn checkpoint_interval(&mut self, rows: usize) -> &mut Self {
+        self.checkpoint_interval = rows.max(1);
+        self
+    }
+
+    /// Geocodes a single query.
+    pub async fn geocode(&self, query: &str) -> Result<Option<Match>, Error> {
+        let resp = match self.client.address(query).get().await {
+            Ok(resp) => resp,
+            Err(e) if is_bad_query(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let count = resp.addresses.len() as f64;
+        if let Some(addr) = resp.addresses.first() {
+            return Ok(Some(address_match(addr, count)));
+        }
+
+        if !self.keyword_fallback {
+            return Ok(None);
+        }
+        let resp = match self.client.keyword(query).get().await {
+            Ok(resp) => resp,
+            Err(e) if is_bad_query(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let count = resp.places.len() as f64;
+        Ok(resp.places.into_iter().next().map(|place| Match {
+            road_address: Some(place.road_address).filter(|addr| !addr.is_empty()),
+            land_lot_address: Some(place.address).filter(|addr| !addr.is_empty()),
+            longitude: place.longitude,
+            latitude: place.latitude,
+            b_code: None,
+            address_type: None,
+            from_keyword: true,
+            confidence: 0.3 / count,
+        }))
+    }
+
+    /// Geocodes `input` into `output`, resuming an earlier run if a checkpoint is found.
+    pub async fn run(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<BatchStats, BatchError> {
+        let output = output.as_ref();
+        let checkpoint_path = checkpoint_path(output);
+
+        let mut reader = csv::Reader::from_path(input)?;
+        let headers = reader.headers()?.clone();
+        let column = headers
+            .iter()
+            .position(|header| header == self.column)
+            .ok_or_else(|| BatchError::MissingColumn(self.column.clone()))?;
+
+        let checkpoint = match fs::read(&checkpoint_path) {
+            Ok(bytes) => Some(serde_json::from_slice::<Checkpoint>(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let (mut file, resumed) = match &checkpoint {
+            Some(checkpoint) => {
+                let file = OpenOptions::new().write(true).open(output)?;
+                if file.metadata()?.len() < checkpoint.offset {
+                    return Err(BatchError::CheckpointMismatch);
+                }
+                file.set_len(checkpoint.offset)?;
+                (file, checkpoint.rows)
+            }
+            None => (File::create(output)?, 0),
+        };
+        file.seek(io::SeekFrom::End(0))?;
+
+        let mut writer = csv::Writer::from_writer(file);
+        if checkpoint.is_none() {
+            writer.write_record(headers.iter().chain(OUTPUT_COLUMNS.iter().copied()))?;
+        }
+
+        let mut stats = BatchStats {
+            resumed,
+            ..BatchStats::default()
+        };
+        let mut results = stream::iter(reader.into_records().skip(resumed))
+            .map(|record| async move {
+                let record = record?;
+                let query = record.get(column).unwrap_or_default().trim();
+                let matched = if query.is_empty() {
+                    None
+                } else {
+                    self.geocode(query).await?
+                };
+                Ok::<_, BatchError>((record, matched))
+            })
+            .buffered(self.concurrency);
+
+        while let Some(result) = results.next().await {
+            let (record, matched) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    save_checkpoint(&mut writer, &checkpoint_path, resumed + stats.rows)?;
+                    return Err(e);
+                }
+            };
+
+            match &matched {
+                Some(m) if m.from_keyword => stats.keyword_matches += 1,
+                Some(_) => stats.address_matches += 1,
+                None => stats.unmatched += 1,
+            }
+            writer.write_record(record.iter().map(str::to_string).chain(columns(&matched)))?;
+            stats.rows += 1;
+
+            if stats.rows % self.checkpoint_interval == 0 {
+                save_checkpoint(&mut writer, &checkpoint_path, resumed + stats.rows)?;
+            }
+        }
+
+        writer.flush()?;
+        match fs::remove_file(&checkpoint_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        Ok(stats)
+    }
+}
+
+fn address_match(addr: &Address, count: f64) -> Match {
+    let (longitude, latitude) = addr.best_coordinate().unzip();
+    let confidence = if addr.road.is_some() || addr.land_lot.is_some() {
+        1.0
+    } else {
+        0.5
+    };
+    Match {
+        road_address: addr.road.as_ref().map(|road| road.address.clone()),
+        land_lot_address: addr
+            .land_lot
+            .as_ref()
+            .map(|land_lot| land_lot.address.clone()),
+        longitude,
+        latitude,
+        b_code: addr.land_lot.as_ref().and_then(|land_lot| land_lot.b_code),
+        address_type: addr.address_type,
+        from_keyword: false,
+        confidence: confidence / count,
+    }
+}
+
+/// Kakao rejects some queries, e.g. ones made only of special characters, which no retry fixes.
+fn is_bad_query(e: &Error) -> bool {
+    e.api_error()
+        .map(|api_error| api_error.is_invalid_argument())
+        .unwrap_or(false)
+}
+
+fn columns(matched: &Option<Match>) -> Vec<String> {
+    let m = match matched {
+        Some(m) => m,
+        None => return vec![String::new(); OUTPUT_COLUMNS.len()],
+    };
+    let match_type = if m.from_keyword {
+        "KEYWORD"
+    } else {
+        m.address_type
+            .map(|address_type| address_type.to_code())
+            .unwrap_or_default()
+    };
+    vec![
+        m.road_address.clone().unwrap_or_default(),
+        m.land_lot_address.clone().unwrap_or_default(),
+        m.longitude.map(|v| v.to_string()).unwrap_or_default(),
+        m.latitude.map(|v| v.to_string()).unwrap_or_default(),
+        m.b_code.map(|v| v.to_string()).unwrap_or_default(),
+        match_type.to_string(),
+        format!("{:.2}", m.confidence),
+    ]
+}
+
+fn checkpoint_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".checkpoint");
+    PathBuf::from(path)
+}
+
+fn save_checkpoint(
+    writer: &mut csv::Writer<File>,
+    path: &Path,
+    rows: usize,
+) -> Result<(), BatchError> {
+    writer.flush()?;
+    writer.get_ref().sync_data()?;
+    let offset = writer.get_ref().metadata()?.len();
+    // Written aside and renamed, so a crash never leaves a truncated checkpoint.
+    let tmp = path.with_extension("checkpoint.tmp");
+    fs::write(&tmp, serde_json::to_vec(&Checkpoint { rows, offset })?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,376 @@
+//! Bulk reverse geocoding.
+//!
+//! A [`ReverseBatch`] looks up the regions and addresses of many points, e.g. GPS pings, with
+//! [`CoordRequest::get_region`](crate::CoordRequest::get_region) and
+//! [`get_address`](crate::CoordRequest::get_address). Points falling in the same cell of a
+//! snapping grid share the result of the first one, so a cluster of pings costs a single lookup.
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! use futures::TryStreamExt;
+//!
+//! let client = daummap::KakaoClient::new(APP_KEY);
+//! let points = daummap::batch::reverse::read_points("pings.ndjson").unwrap();
+//! let matches = daummap::batch::reverse::ReverseBatch::new(&client)
+//!     .snap_grid(20.0)
+//!     .try_stream(points)
+//!     .try_collect::<Vec<_>>()
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+
+use {
+    crate::{
+        batch::BatchError, Error, KakaoClient, LandLotAddress, Region, RegionType, RoadAddress,
+    },
+    futures::{
+        future,
+        stream::{self, Stream, StreamExt},
+    },
+    serde_json::Value,
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+        fs::{self, File},
+        io::{BufRead, BufReader},
+        path::Path,
+    },
+};
+
+/// Length of a degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Points read ahead of the output, to find the cells to look up concurrently.
+const CHUNK_PER_REQUEST: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReversePoint {
+    pub id: Option<String>,
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReverseMatch {
+    pub point: ReversePoint,
+    /// Administrative region (행정동), whose code is the H code.
+    pub h_region: Option<Region>,
+    /// Legal region (법정동), whose code is the B code.
+    pub b_region: Option<Region>,
+    pub road: Option<RoadAddress>,
+    pub land_lot: Option<LandLotAddress>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReverseBatch<'a> {
+    client: &'a KakaoClient,
+    concurrency: usize,
+    grid: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct Lookup {
+    h_region: Option<Region>,
+    b_region: Option<Region>,
+    road: Option<RoadAddress>,
+    land_lot: Option<LandLotAddress>,
+}
+
+/// Points read from a file by [`read_points`], one at a time.
+pub struct Points {
+    inner: Box<dyn Iterator<Item = Result<ReversePoint, BatchError>> + Send>,
+}
+
+type Cell = (i64, i64);
+
+struct State<I> {
+    points: I,
+    cache: HashMap<Cell, Lookup>,
+    done: bool,
+}
+
+impl<'a> ReverseBatch<'a> {
+    pub fn new(client: &'a KakaoClient) -> Self {
+        ReverseBatch {
+            client,
+            concurrency: 4,
+            grid: None,
+        }
+    }
+
+    /// Number of points looked up at once. Defaults to 4.
+    pub fn concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Size of the snapping grid in meters. Without a grid, only points with the very same
+    /// coordinate share a lookup.
+    pub fn snap_grid(&mut self, meters: f64) -> &mut Self {
+        self.grid = Some(meters).filter(|meters| *meters > 0.0);
+        self
+    }
+
+    /// Streams the matches of `points`, in order. Results are kept per grid cell for the whole
+    /// stream. The stream ends after the first error.
+    pub fn stream<'b, I>(
+        &'b self,
+        points: I,
+    ) -> impl Stream<Item = Result<ReverseMatch, Error>> + 'b
+    where
+        I: IntoIterator<Item = ReversePoint>,
+        I::IntoIter: 'b,
+    {
+        self.try_stream(points.into_iter().map(Ok))
+    }
+
+    /// Same as [`stream`](Self::stream) for points that may fail to be read, like those of
+    /// [`read_points`]. Points are read as the stream is polled, and the stream ends after the
+    /// first point that cannot be read.
+    pub fn try_stream<'b, I, E>(
+        &'b self,
+        points: I,
+    ) -> impl Stream<Item = Result<ReverseMatch, E>> + 'b
+    where
+        I: IntoIterator<Item = Result<ReversePoint, E>>,
+        I::IntoIter: 'b,
+        E: From<Error> + 'b,
+    {
+        let state = State {
+            points: points.into_iter(),
+            cache: HashMap::new(),
+            done: false,
+        };
+        let chunk_size = self.concurrency * CHUNK_PER_REQUEST;
+
+        stream::unfold(state, move |mut state| async move {
+            if state.done {
+                return None;
+            }
+            let mut chunk = Vec::with_capacity(chunk_size);
+            let mut unreadable = None;
+            for point in state.points.by_ref().take(chunk_size) {
+                match point {
+                    Ok(point) => chunk.push(point),
+                    Err(e) => {
+                        unreadable = Some(e);
+                        state.done = true;
+                        break;
+                    }
+                }
+            }
+            if chunk.is_empty() && unreadable.is_none() {
+                return None;
+            }
+
+            let mut queued = HashSet::new();
+            let missing = chunk
+                .iter()
+                .map(|point| (self.cell(point), point))
+                .filter(|(cell, _)| !state.cache.contains_key(cell) && queued.insert(*cell))
+                .map(|(cell, point)| (cell, point.longitude, point.latitude))
+                .collect::<Vec<_>>();
+            let fetched = stream::iter(missing)
+                .map(|(cell, longitude, latitude)| async move {
+                    (cell, self.lookup(longitude, latitude).await)
+                })
+                .buffer_unordered(self.concurrency)
+                .collect::<Vec<_>>()
+                .await;
+
+            let mut failed = HashMap::new();
+            for (cell, result) in fetched {
+                match result {
+                    Ok(lookup) => {
+                        state.cache.insert(cell, lookup);
+                    }
+                    Err(e) => {
+                        failed.insert(cell, e);
+                    }
+                }
+            }
+
+            let mut items = Vec::with_capacity(chunk.len() + 1);
+            for point in chunk {
+                let cell = self.cell(&point);
+                if let Some(e) = failed.remove(&cell) {
+                    items.push(Err(e.into()));
+                    state.done = true;
+                    unreadable = None;
+                    break;
+                }
+                let lookup = state.cache[&cell].clone();
+                items.push(Ok(ReverseMatch {
+                    point,
+                    h_region: lookup.h_region,
+                    b_region: lookup.b_region,
+                    road: lookup.road,
+                    land_lot: lookup.land_lot,
+                }));
+            }
+            items.extend(unreadable.map(Err));
+            Some((stream::iter(items), state))
+        })
+        .flatten()
+    }
+
+    async fn lookup(&self, longitude: f64, latitude: f64) -> Result<Lookup, Error> {
+        let req = self.client.coord(longitude, latitude);
+        let (regions, addresses) = future::try_join(req.get_region(), req.get_address()).await?;
+
+        let region = |region_type| {
+            regions
+                .iter()
+                .find(|region| region.region_type == Some(region_type))
+                .cloned()
+        };
+        let address = addresses.into_iter().next();
+        Ok(Lookup {
+            h_region: region(RegionType::Administrative),
+            b_region: region(RegionType::Legal),
+            road: address.as_ref().and_then(|address| address.road.clone()),
+            land_lot: address.and_then(|address| address.land_lot),
+        })
+    }
+
+    fn cell(&self, point: &ReversePoint) -> Cell {
+        match self.grid {
+            None => (
+                point.longitude.to_bits() as i64,
+                point.latitude.to_bits() as i64,
+            ),
+            Some(meters) => {
+                let lat_step = meters / METERS_PER_DEGREE;
+                let row = (point.latitude / lat_step).floor();
+                // Cells of a row are as wide as they are high at the center of the row.
+                let center = ((row + 0.5) * lat_step).to_radians().cos().max(0.01);
+                let lon_step = lat_step / center;
+                ((point.longitude / lon_step).floor() as i64, row as i64)
+            }
+        }
+    }
+}
+
+/// Reads points from a CSV, NDJSON or GeoJSON file, told apart by the extension.
+///
+/// CSV files need `x`/`y`, `longitude`/`latitude` or `lon`/`lat` columns and may have an `id`
+/// column. NDJSON lines are objects with the same keys, or GeoJSON point features. GeoJSON files
+/// are feature collections of points; ids are taken from the feature or its `id` property.
+///
+/// CSV and NDJSON files are read a record at a time as the points are iterated, so errors in the
+/// records come from the iterator. GeoJSON files are parsed whole.
+pub fn read_points(path: impl AsRef<Path>) -> Result<Points, BatchError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let inner: Box<dyn Iterator<Item = _> + Send> = match extension.as_str() {
+        "ndjson" | "jsonl" => Box::new(
+            BufReader::new(File::open(path)?)
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+                .map(|(i, line)| {
+                    let value = serde_json::from_str(&line?).map_err(|source| {
+                        BatchError::InvalidInput {
+                            line: i + 1,
+                            source,
+                        }
+                    })?;
+                    json_point(&value).ok_or(BatchError::InvalidPoint(i + 1))
+                }),
+        ),
+        "geojson" | "json" => {
+            let mut value =
+                serde_json::from_slice::<Value>(&fs::read(path)?).map_err(|source| {
+                    BatchError::InvalidInput {
+                        line: source.line(),
+                        source,
+                    }
+                })?;
+            let features = match value["features"].take() {
+                Value::Array(features) => features,
+                _ => return Err(BatchError::MissingFeatures),
+            };
+            Box::new(
+                features.into_iter().enumerate().map(|(i, feature)| {
+                    json_point(&feature).ok_or(BatchError::InvalidPoint(i + 1))
+                }),
+            )
+        }
+        _ => {
+            let mut reader = csv::Reader::from_path(path)?;
+            let headers = reader.headers()?.clone();
+            let column = |names: &[&str]| headers.iter().position(|header| names.contains(&header));
+            let x = column(&["x", "longitude", "lon", "lng"])
+                .ok_or_else(|| BatchError::MissingColumn("x".to_string()))?;
+            let y = column(&["y", "latitude", "lat"])
+                .ok_or_else(|| BatchError::MissingColumn("y".to_string()))?;
+            let id = column(&["id"]);
+
+            Box::new(reader.into_records().enumerate().map(move |(i, record)| {
+                let record = record?;
+                let coord = |column: usize| record.get(column)?.trim().parse::<f64>().ok();
+                match (coord(x), coord(y)) {
+                    (Some(longitude), Some(latitude)) => Ok(ReversePoint {
+                        id: id.and_then(|id| record.get(id)).map(str::to_string),
+                        longitude,
+                        latitude,
+                    }),
+                    // Line 1 is the header.
+                    _ => Err(BatchError::InvalidPoint(i + 2)),
+                }
+            }))
+        }
+    };
+    Ok(Points { inner })
+}
+
+impl Iterator for Points {
+    type Item = Result<ReversePoint, BatchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl fmt::Debug for Points {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Points").finish_non_exhaustive()
+    }
+}
+
+fn json_point(value: &Value) -> Option<ReversePoint> {
+    let id = |value: &Value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+
+    if value["type"] == "Feature" {
+        let geometry = &value["geometry"];
+        if geometry["type"] != "Point" {
+            return None;
+        }
+        return Some(ReversePoint {
+            id: id(&value["id"]).or_else(|| id(&value["properties"]["id"])),
+            longitude: geometry["coordinates"][0].as_f64()?,
+            latitude: geometry["coordinates"][1].as_f64()?,
+        });
+    }
+
+    let coord = |names: &[&str]| names.iter().find_map(|name| value[*name].as_f64());
+    Some(ReversePoint {
+        id: id(&value["id"]),
+        longitude: coord(&["x", "longitude", "lon", "lng"])?,
+        latitude: coord(&["y", "latitude", "lat"])?,
+    })
+}
@@ -0,0 +1,683 @@
+//! Command-line client for ad-hoc geocoding and place lookups.
+//!
+//! ```text
+//! $ export KAKAO_REST_API_KEY=...
+//! $ daummap keyword 카카오프렌즈 --x 127.06 --y 37.51 --radius 2000 --format csv
+//! $ daummap region 127.10459896729914 37.40269721785548
+//! ```
+
+use {
+    clap::{Args, Parser, Subcommand, ValueEnum},
+    daummap::{
+        batch::{
+            reverse::{read_points, ReverseBatch, ReverseMatch},
+            Batch,
+        },
+        cache::{FileStore, ResponseCache},
+        Address, AnalyzeType, CategoryGroup, CoordSystem, Error, KakaoClient, Place, Region, Sort,
+        TransCoord,
+    },
+    futures::{pin_mut, Stream, TryStreamExt},
+    geojson::{Feature, FeatureCollection, Geometry, Value},
+    serde::{ser::SerializeSeq, Serialize, Serializer},
+    std::{
+        error::Error as StdError,
+        io::{self, Write},
+        path::PathBuf,
+        process,
+    },
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "daummap", version, about = "Query the Kakao Local API")]
+struct Opt {
+    /// Kakao REST API key.
+    #[arg(long, env = "KAKAO_REST_API_KEY", hide_env_values = true)]
+    app_key: String,
+
+    /// Base URL of the API, e.g. a local mock server.
+    #[arg(long, global = true)]
+    base_url: Option<String>,
+
+    /// Directory caching the responses between runs.
+    #[arg(long, global = true, env = "DAUMMAP_CACHE_DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Answer only from the cache directory.
+    #[arg(long, global = true, requires = "cache_dir")]
+    offline: bool,
+
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Geojson,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Geocode an address.
+    Address {
+        query: String,
+        #[arg(long, value_enum)]
+        analyze_type: Option<AnalyzeTypeArg>,
+        #[command(flatten)]
+        paging: Paging,
+    },
+    /// Search places by keyword.
+    Keyword {
+        query: String,
+        /// Category group code, e.g. FD6.
+        #[arg(long)]
+        category: Option<CategoryGroup>,
+        #[command(flatten)]
+        area: Area,
+        #[command(flatten)]
+        paging: Paging,
+    },
+    /// Search places of a category group in a circle or a rectangle.
+    Category {
+        /// Category group code, e.g. FD6.
+        category: CategoryGroup,
+        #[command(flatten)]
+        area: Area,
+        #[command(flatten)]
+        paging: Paging,
+    },
+    /// Administrative and legal regions of a coordinate.
+    #[command(allow_negative_numbers = true)]
+    Region {
+        x: f64,
+        y: f64,
+        #[arg(long)]
+        input_coord: Option<CoordSystem>,
+        #[arg(long)]
+        output_coord: Option<CoordSystem>,
+    },
+    /// Land-lot and road addresses of a coordinate.
+    #[command(allow_negative_numbers = true)]
+    Reverse {
+        x: f64,
+        y: f64,
+        #[arg(long)]
+        input_coord: Option<CoordSystem>,
+    },
+    /// Convert a coordinate between coordinate systems.
+    #[command(allow_negative_numbers = true)]
+    Transcoord {
+        x: f64,
+        y: f64,
+        #[arg(long, default_value = "WGS84")]
+        input_coord: CoordSystem,
+        #[arg(long, default_value = "WGS84")]
+        output_coord: CoordSystem,
+    },
+    /// Geocode a column of a CSV file. An interrupted run resumes where it stopped.
+    Batch {
+        input: PathBuf,
+        output: PathBuf,
+        /// Column holding the addresses.
+        #[arg(long, default_value = "address")]
+        column: String,
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// Leave rows unmatched instead of searching by keyword.
+        #[arg(long)]
+        no_keyword_fallback: bool,
+    },
+    /// Regions and addresses of the points of a CSV, NDJSON or GeoJSON file.
+    ReverseBatch {
+        input: PathBuf,
+        /// Size of the snapping grid in meters; points in one cell share a lookup.
+        #[arg(long)]
+        grid: Option<f64>,
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum AnalyzeTypeArg {
+    Similar,
+    Exact,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SortArg {
+    Accuracy,
+    Distance,
+}
+
+#[derive(Debug, Args)]
+struct Area {
+    /// Longitude of the center.
+    #[arg(long, requires = "y", allow_hyphen_values = true)]
+    x: Option<f64>,
+    /// Latitude of the center.
+    #[arg(long, requires = "x", allow_hyphen_values = true)]
+    y: Option<f64>,
+    /// Radius around the center in meters.
+    #[arg(long, requires = "x")]
+    radius: Option<usize>,
+    /// Bounding box as x1,y1,x2,y2.
+    #[arg(
+        long,
+        value_parser = parse_rect,
+        allow_hyphen_values = true,
+        conflicts_with_all = ["x", "radius"]
+    )]
+    rect: Option<(f64, f64, f64, f64)>,
+    #[arg(long, value_enum)]
+    sort: Option<SortArg>,
+}
+
+#[derive(Debug, Args)]
+struct Paging {
+    #[arg(long, default_value_t = 1)]
+    page: usize,
+    #[arg(long)]
+    size: Option<usize>,
+    /// Fetch every page from `--page` on.
+    #[arg(long)]
+    all: bool,
+}
+
+/// A result printable in every output format.
+trait Record: Serialize {
+    const COLUMNS: &'static [&'static str];
+
+    fn values(&self) -> Vec<String>;
+
+    fn feature(&self) -> Feature;
+}
+
+impl Record for Place {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "category",
+        "phone",
+        "address",
+        "road_address",
+        "longitude",
+        "latitude",
+        "distance",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            show(&self.id),
+            self.name.clone(),
+            self.category.clone(),
+            self.phone.clone(),
+            self.address.clone(),
+            self.road_address.clone(),
+            show(&self.longitude),
+            show(&self.latitude),
+            show(&self.distance),
+        ]
+    }
+
+    fn feature(&self) -> Feature {
+        self.to_geojson_feature()
+    }
+}
+
+impl Record for Address {
+    const COLUMNS: &'static [&'static str] = &[
+        "address",
+        "address_type",
+        "land_lot_address",
+        "road_address",
+        "longitude",
+        "latitude",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        let (longitude, latitude) = self.best_coordinate().unzip();
+        vec![
+            self.address.clone().unwrap_or_default(),
+            show(&self.address_type.map(|address_type| address_type.to_code())),
+            show(&self.land_lot.as_ref().map(|addr| &addr.address)),
+            show(&self.road.as_ref().map(|addr| &addr.address)),
+            show(&longitude),
+            show(&latitude),
+        ]
+    }
+
+    fn feature(&self) -> Feature {
+        self.to_geojson_feature()
+    }
+}
+
+impl Record for Region {
+    const COLUMNS: &'static [&'static str] = &[
+        "code",
+        "address",
+        "province",
+        "city",
+        "town",
+        "neighborhood",
+        "longitude",
+        "latitude",
+        "coord_system",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            show(&self.code),
+            self.address.clone(),
+            self.province.clone(),
+            self.city.clone(),
+            self.town.clone(),
+            self.neighborhood.clone(),
+            show(&self.longitude),
+            show(&self.latitude),
+            self.coord_system.to_string(),
+        ]
+    }
+
+    fn feature(&self) -> Feature {
+        self.to_geojson_feature()
+    }
+}
+
+impl Record for TransCoord {
+    const COLUMNS: &'static [&'static str] = &["x", "y", "coord_system"];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.x.to_string(),
+            self.y.to_string(),
+            self.coord_system.to_string(),
+        ]
+    }
+
+    fn feature(&self) -> Feature {
+        let mut properties = geojson::JsonObject::new();
+        properties.insert(
+            "coord_system".to_string(),
+            self.coord_system.to_code().into(),
+        );
+        Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(Value::Point(vec![self.x, self.y]))),
+            id: None,
+            properties: Some(properties),
+            foreign_members: None,
+        }
+    }
+}
+
+impl Record for ReverseMatch {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "longitude",
+        "latitude",
+        "h_code",
+        "h_region",
+        "b_code",
+        "b_region",
+        "road_address",
+        "land_lot_address",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.point.id.clone().unwrap_or_default(),
+            self.point.longitude.to_string(),
+            self.point.latitude.to_string(),
+            show(&self.h_region.as_ref().and_then(|region| region.code)),
+            show(&self.h_region.as_ref().map(|region| &region.address)),
+            show(&self.b_region.as_ref().and_then(|region| region.code)),
+            show(&self.b_region.as_ref().map(|region| &region.address)),
+            show(&self.road.as_ref().map(|addr| &addr.address)),
+            show(&self.land_lot.as_ref().map(|addr| &addr.address)),
+        ]
+    }
+
+    fn feature(&self) -> Feature {
+        let properties = Self::COLUMNS
+            .iter()
+            .map(|column| column.to_string())
+            .zip(self.values().into_iter().map(Into::into))
+            .filter(|(column, _)| column != "longitude" && column != "latitude")
+            .collect();
+        Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(Value::Point(vec![
+                self.point.longitude,
+                self.point.latitude,
+            ]))),
+            id: None,
+            properties: Some(properties),
+            foreign_members: None,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::parse();
+    if let Err(e) = run(opt).await {
+        eprintln!("error: {}", e);
+        let mut source = e.source();
+        while let Some(e) = source {
+            eprintln!("  caused by: {}", e);
+            source = e.source();
+        }
+        process::exit(1);
+    }
+}
+
+async fn run(opt: Opt) -> Result<(), Box<dyn StdError>> {
+    let mut builder = KakaoClient::builder(&opt.app_key);
+    if let Some(base_url) = &opt.base_url {
+        builder.base_url(base_url);
+    }
+    if let Some(cache_dir) = &opt.cache_dir {
+        let mut cache = ResponseCache::with_store(FileStore::new(cache_dir)?);
+        cache.offline(opt.offline);
+        builder.cache(cache);
+    }
+    let client = builder.build()?;
+    let format = opt.format;
+
+    match opt.command {
+        Command::Address {
+            query,
+            analyze_type,
+            paging,
+        } => {
+            let mut req = client.address(&query);
+            req.page(paging.page);
+            if let Some(size) = paging.size {
+                req.size(size);
+            }
+            if let Some(analyze_type) = analyze_type {
+                req.analyze_type(match analyze_type {
+                    AnalyzeTypeArg::Similar => AnalyzeType::Similar,
+                    AnalyzeTypeArg::Exact => AnalyzeType::Exact,
+                });
+            }
+            let addresses = if paging.all {
+                collect(req.stream()).await?
+            } else {
+                req.get().await?.addresses
+            };
+            print(format, &addresses)
+        }
+        Command::Keyword {
+            query,
+            category,
+            area,
+            paging,
+        } => {
+            let mut req = client.keyword(&query);
+            req.page(paging.page);
+            if let Some(size) = paging.size {
+                req.size(size);
+            }
+            if let Some(category) = category {
+                req.category_group(category);
+            }
+            if let (Some(x), Some(y)) = (area.x, area.y) {
+                req.coord(x, y);
+            }
+            if let Some(radius) = area.radius {
+                req.radius(radius);
+            }
+            if let Some((x1, y1, x2, y2)) = area.rect {
+                req.rect(x1, y1, x2, y2);
+            }
+            if let Some(sort) = area.sort {
+                req.sort(sort.into());
+            }
+            let places = if paging.all {
+                collect(req.stream()).await?
+            } else {
+                req.get().await?.places
+            };
+            print(format, &places)
+        }
+        Command::Category {
+            category,
+            area,
+            paging,
+        } => {
+            let mut req = match (area.rect, area.x, area.y) {
+                (Some((x1, y1, x2, y2)), _, _) => client.category_rect(category, x1, y1, x2, y2),
+                (None, Some(x), Some(y)) => {
+                    client.category_circle(category, x, y, area.radius.unwrap_or(20000))
+                }
+                _ => return Err("either --x and --y or --rect is required".into()),
+            };
+            req.page(paging.page);
+            if let Some(size) = paging.size {
+                req.size(size);
+            }
+            if let Some(sort) = area.sort {
+                req.sort(sort.into());
+            }
+            let places = if paging.all {
+                collect(req.stream()).await?
+            } else {
+                req.get().await?.places
+            };
+            print(format, &places)
+        }
+        Command::Region {
+            x,
+            y,
+            input_coord,
+            output_coord,
+        } => {
+            let mut req = client.coord(x, y);
+            if let Some(input_coord) = input_coord {
+                req.input_coord(input_coord);
+            }
+            if let Some(output_coord) = output_coord {
+                req.output_coord(output_coord);
+            }
+            print(format, &req.get_region().await?)
+        }
+        Command::Reverse { x, y, input_coord } => {
+            let mut req = client.coord(x, y);
+            if let Some(input_coord) = input_coord {
+                req.input_coord(input_coord);
+            }
+            print(format, &req.get_address().await?)
+        }
+        Command::Transcoord {
+            x,
+            y,
+            input_coord,
+            output_coord,
+        } => {
+            let coords = client
+                .transcoord(x, y)
+                .input_coord(input_coord)
+                .output_coord(output_coord)
+                .get()
+                .await?;
+            print(format, &coords)
+        }
+        Command::Batch {
+            input,
+            output,
+            column,
+            concurrency,
+            no_keyword_fallback,
+        } => {
+            let stats = Batch::new(&client, &column)
+                .concurrency(concurrency)
+                .keyword_fallback(!no_keyword_fallback)
+                .run(input, output)
+                .await?;
+            eprintln!(
+                "{} rows ({} resumed): {} address matches, {} keyword matches, {} unmatched",
+                stats.rows,
+                stats.resumed,
+                stats.address_matches,
+                stats.keyword_matches,
+                stats.unmatched
+            );
+            Ok(())
+        }
+        Command::ReverseBatch {
+            input,
+            grid,
+            concurrency,
+        } => {
+            let points = read_points(input)?;
+            let mut batch = ReverseBatch::new(&client);
+            batch.concurrency(concurrency);
+            if let Some(grid) = grid {
+                batch.snap_grid(grid);
+            }
+            print_stream(format, batch.try_stream(points)).await
+        }
+    }
+}
+
+impl From<SortArg> for Sort {
+    fn from(sort: SortArg) -> Self {
+        match sort {
+            SortArg::Accuracy => Sort::Accuracy,
+            SortArg::Distance => Sort::Distance,
+        }
+    }
+}
+
+async fn collect<T>(stream: impl Stream<Item = Result<T, Error>>) -> Result<Vec<T>, Error> {
+    stream.try_collect().await
+}
+
+fn print<T: Record>(format: Format, records: &[T]) -> Result<(), Box<dyn StdError>> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match format {
+        Format::Table => {
+            let rows = records.iter().map(Record::values).collect::<Vec<_>>();
+            let mut widths = T::COLUMNS
+                .iter()
+                .map(|column| column.chars().count())
+                .collect::<Vec<_>>();
+            for row in &rows {
+                for (width, value) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(value.chars().count());
+                }
+            }
+            let header = T::COLUMNS.iter().map(|column| column.to_string()).collect();
+            for row in Some(header).into_iter().chain(rows) {
+                let line = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(value, &width)| format!("{:width$}", value, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                writeln!(out, "{}", line.trim_end())?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)?;
+        }
+        Format::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut out, record)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(T::COLUMNS)?;
+            for record in records {
+                writer.write_record(record.values())?;
+            }
+            writer.flush()?;
+        }
+        Format::Geojson => {
+            let collection = FeatureCollection {
+                bbox: None,
+                features: records.iter().map(Record::feature).collect(),
+                foreign_members: None,
+            };
+            serde_json::to_writer_pretty(&mut out, &collection)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints records as they arrive. Tables are aligned on every row and GeoJSON features are
+/// wrapped in a collection, so both are printed once the stream ends.
+async fn print_stream<T, E>(
+    format: Format,
+    stream: impl Stream<Item = Result<T, E>>,
+) -> Result<(), Box<dyn StdError>>
+where
+    T: Record,
+    E: StdError + 'static,
+{
+    pin_mut!(stream);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match format {
+        Format::Table | Format::Geojson => {
+            let records = stream.try_collect::<Vec<_>>().await?;
+            drop(out);
+            return print(format, &records);
+        }
+        Format::Json => {
+            let mut serializer = serde_json::Serializer::pretty(&mut out);
+            let mut seq = serializer.serialize_seq(None)?;
+            while let Some(record) = stream.try_next().await? {
+                seq.serialize_element(&record)?;
+            }
+            seq.end()?;
+            writeln!(out)?;
+        }
+        Format::Ndjson => {
+            while let Some(record) = stream.try_next().await? {
+                serde_json::to_writer(&mut out, &record)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(T::COLUMNS)?;
+            while let Some(record) = stream.try_next().await? {
+                writer.write_record(record.values())?;
+                writer.flush()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn show<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+fn parse_rect(s: &str) -> Result<(f64, f64, f64, f64), String> {
+    let values = s
+        .split(',')
+        .map(|v| v.trim().parse::<f64>().map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match values[..] {
+        [x1, y1, x2, y2] => Ok((x1, y1, x2, y2)),
+        _ => Err("expected x1,y1,x2,y2".to_string()),
+    }
+}
@@ -0,0 +1,527 @@
+//! Synchronous API for programs without an async runtime.
+//!
+//! A [`KakaoClient`] here wraps an async [`crate::KakaoClient`] and a single-threaded Tokio
+//! runtime, and its request builders wait for the async ones to finish. Requests are sent,
+//! retried, rate limited, cached and parsed exactly as with the async API.
+//!
+//! The request builders mirror the async ones, standalone constructors included, so synchronous
+//! code can switch over by changing the module path. A request built with a standalone
+//! constructor starts a runtime of its own.
+//!
+//! The blocking methods must not be called from within an async runtime.
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # fn foo() {
+//! # let APP_KEY = "";
+//! let client = daummap::blocking::KakaoClient::new(APP_KEY);
+//! let resp = client.address("전북 삼성동 100").get().unwrap();
+//! for addr in resp.addresses {
+//!     println!("{:?}", addr.best_coordinate());
+//! }
+//!
+//! let resp = daummap::blocking::KeywordRequest::new(APP_KEY, "카카오프렌즈")
+//!     .get()
+//!     .unwrap();
+//! # }
+//! ```
+
+use {
+    crate::{
+        proj::Projection, Address, AddressResponse, AnalyzeType, CategoryGroup, CategoryResponse,
+        CoordSystem, Error, KeywordResponse, Region, Sort, TransCoord,
+    },
+    std::sync::Arc,
+    tokio::runtime::{Builder, Runtime},
+};
+
+/// A reusable handle to the Kakao Local API that blocks the calling thread.
+///
+/// Cloning is cheap and clones share the same async client and runtime.
+#[derive(Debug, Clone)]
+pub struct KakaoClient {
+    inner: crate::KakaoClient,
+    runtime: Arc<Runtime>,
+}
+
+#[derive(Debug)]
+pub struct AddressRequest<'a> {
+    inner: crate::AddressRequest<'a>,
+    runtime: Arc<Runtime>,
+}
+
+#[derive(Debug)]
+pub struct KeywordRequest<'a> {
+    inner: crate::KeywordRequest<'a>,
+    runtime: Arc<Runtime>,
+}
+
+#[derive(Debug)]
+pub struct CategoryRequest<'a> {
+    inner: crate::CategoryRequest<'a>,
+    runtime: Arc<Runtime>,
+}
+
+#[derive(Debug)]
+pub struct CoordRequest<'a> {
+    inner: crate::CoordRequest<'a>,
+    runtime: Arc<Runtime>,
+}
+
+#[derive(Debug)]
+pub struct TransCoordRequest<'a> {
+    inner: crate::TransCoordRequest<'a>,
+    runtime: Arc<Runtime>,
+}
+
+/// # Panics
+///
+/// Panics if the runtime cannot be started.
+fn new_runtime() -> Arc<Runtime> {
+    let runtime = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("cannot start the blocking runtime");
+    Arc::new(runtime)
+}
+
+impl KakaoClient {
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn new(app_key: &str) -> Self {
+        crate::KakaoClient::new(app_key).into()
+    }
+
+    /// The async client requests are sent through.
+    pub fn as_async(&self) -> &crate::KakaoClient {
+        &self.inner
+    }
+
+    pub fn address(&self, query: &str) -> AddressRequest<'_> {
+        AddressRequest {
+            inner: self.inner.address(query),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn keyword(&self, query: &str) -> KeywordRequest<'_> {
+        KeywordRequest {
+            inner: self.inner.keyword(query),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn category_circle(
+        &self,
+        category_group: CategoryGroup,
+        longitude: f64,
+        latitude: f64,
+        radius: usize,
+    ) -> CategoryRequest<'_> {
+        CategoryRequest {
+            inner: self
+                .inner
+                .category_circle(category_group, longitude, latitude, radius),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn category_rect(
+        &self,
+        category_group: CategoryGroup,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> CategoryRequest<'_> {
+        CategoryRequest {
+            inner: self.inner.category_rect(category_group, x1, y1, x2, y2),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn category_rect_projected(
+        &self,
+        category_group: CategoryGroup,
+        projection: Projection,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> CategoryRequest<'_> {
+        CategoryRequest {
+            inner: self
+                .inner
+                .category_rect_projected(category_group, projection, x1, y1, x2, y2),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn coord(&self, longitude: f64, latitude: f64) -> CoordRequest<'_> {
+        CoordRequest {
+            inner: self.inner.coord(longitude, latitude),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn coord_projected(&self, projection: Projection, x: f64, y: f64) -> CoordRequest<'_> {
+        CoordRequest {
+            inner: self.inner.coord_projected(projection, x, y),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn transcoord(&self, x: f64, y: f64) -> TransCoordRequest<'_> {
+        TransCoordRequest {
+            inner: self.inner.transcoord(x, y),
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+/// Wraps a client built with [`crate::KakaoClientBuilder`].
+///
+/// # Panics
+///
+/// Panics if the runtime cannot be started.
+impl From<crate::KakaoClient> for KakaoClient {
+    fn from(inner: crate::KakaoClient) -> Self {
+        KakaoClient {
+            inner,
+            runtime: new_runtime(),
+        }
+    }
+}
+
+impl AddressRequest<'static> {
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn new(app_key: &str, query: &str) -> Self {
+        AddressRequest {
+            inner: crate::AddressRequest::new(app_key, query),
+            runtime: new_runtime(),
+        }
+    }
+}
+
+impl<'a> AddressRequest<'a> {
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.inner.base_url(base_url);
+        self
+    }
+
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.inner.no_cache();
+        self
+    }
+
+    pub fn page(&mut self, page: usize) -> &mut Self {
+        self.inner.page(page);
+        self
+    }
+
+    pub fn size(&mut self, size: usize) -> &mut Self {
+        self.inner.size(size);
+        self
+    }
+
+    pub fn analyze_type(&mut self, analyze_type: AnalyzeType) -> &mut Self {
+        self.inner.analyze_type(analyze_type);
+        self
+    }
+
+    pub fn get(&self) -> Result<AddressResponse, Error> {
+        self.runtime.block_on(self.inner.get())
+    }
+}
+
+impl KeywordRequest<'static> {
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn new(app_key: &str, query: &str) -> Self {
+        KeywordRequest {
+            inner: crate::KeywordRequest::new(app_key, query),
+            runtime: new_runtime(),
+        }
+    }
+}
+
+impl<'a> KeywordRequest<'a> {
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.inner.base_url(base_url);
+        self
+    }
+
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.inner.no_cache();
+        self
+    }
+
+    pub fn category_group(&mut self, group: CategoryGroup) -> &mut Self {
+        self.inner.category_group(group);
+        self
+    }
+
+    pub fn longitude(&mut self, x: f64) -> &mut Self {
+        self.inner.longitude(x);
+        self
+    }
+
+    pub fn latitude(&mut self, y: f64) -> &mut Self {
+        self.inner.latitude(y);
+        self
+    }
+
+    pub fn coord(&mut self, longitude: f64, latitude: f64) -> &mut Self {
+        self.inner.coord(longitude, latitude);
+        self
+    }
+
+    pub fn radius(&mut self, r: usize) -> &mut Self {
+        self.inner.radius(r);
+        self
+    }
+
+    pub fn rect(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) -> &mut Self {
+        self.inner.rect(x1, y1, x2, y2);
+        self
+    }
+
+    pub fn rect_projected(
+        &mut self,
+        projection: Projection,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> &mut Self {
+        self.inner.rect_projected(projection, x1, y1, x2, y2);
+        self
+    }
+
+    pub fn page(&mut self, page: usize) -> &mut Self {
+        self.inner.page(page);
+        self
+    }
+
+    pub fn size(&mut self, size: usize) -> &mut Self {
+        self.inner.size(size);
+        self
+    }
+
+    pub fn sort(&mut self, sort: Sort) -> &mut Self {
+        self.inner.sort(sort);
+        self
+    }
+
+    pub fn get(&self) -> Result<KeywordResponse, Error> {
+        self.runtime.block_on(self.inner.get())
+    }
+}
+
+impl CategoryRequest<'static> {
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn circle(
+        app_key: &str,
+        category_group: CategoryGroup,
+        longitude: f64,
+        latitude: f64,
+        radius: usize,
+    ) -> Self {
+        CategoryRequest {
+            inner: crate::CategoryRequest::circle(
+                app_key,
+                category_group,
+                longitude,
+                latitude,
+                radius,
+            ),
+            runtime: new_runtime(),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn rect(
+        app_key: &str,
+        category_group: CategoryGroup,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Self {
+        CategoryRequest {
+            inner: crate::CategoryRequest::rect(app_key, category_group, x1, y1, x2, y2),
+            runtime: new_runtime(),
+        }
+    }
+
+    /// Like [`rect`](Self::rect), with corners in another system. The rectangle is converted to
+    /// the WGS84 box enclosing it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn rect_projected(
+        app_key: &str,
+        category_group: CategoryGroup,
+        projection: Projection,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Self {
+        CategoryRequest {
+            inner: crate::CategoryRequest::rect_projected(
+                app_key,
+                category_group,
+                projection,
+                x1,
+                y1,
+                x2,
+                y2,
+            ),
+            runtime: new_runtime(),
+        }
+    }
+}
+
+impl<'a> CategoryRequest<'a> {
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.inner.base_url(base_url);
+        self
+    }
+
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.inner.no_cache();
+        self
+    }
+
+    pub fn page(&mut self, page: usize) -> &mut Self {
+        self.inner.page(page);
+        self
+    }
+
+    pub fn size(&mut self, size: usize) -> &mut Self {
+        self.inner.size(size);
+        self
+    }
+
+    pub fn sort(&mut self, sort: Sort) -> &mut Self {
+        self.inner.sort(sort);
+        self
+    }
+
+    pub fn get(&self) -> Result<CategoryResponse, Error> {
+        self.runtime.block_on(self.inner.get())
+    }
+}
+
+impl CoordRequest<'static> {
+    /// With [`input_coord`](CoordRequest::input_coord) set, `longitude` and `latitude` are the x
+    /// and y of that coordinate system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn new(app_key: &str, longitude: f64, latitude: f64) -> Self {
+        CoordRequest {
+            inner: crate::CoordRequest::new(app_key, longitude, latitude),
+            runtime: new_runtime(),
+        }
+    }
+
+    /// Like [`new`](Self::new), with a coordinate in another system converted to WGS84 locally.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn projected(app_key: &str, projection: Projection, x: f64, y: f64) -> Self {
+        CoordRequest {
+            inner: crate::CoordRequest::projected(app_key, projection, x, y),
+            runtime: new_runtime(),
+        }
+    }
+}
+
+impl<'a> CoordRequest<'a> {
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.inner.base_url(base_url);
+        self
+    }
+
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.inner.no_cache();
+        self
+    }
+
+    pub fn page(&mut self, page: usize) -> &mut Self {
+        self.inner.page(page);
+        self
+    }
+
+    /// Coordinate system of the given coordinate. Kakao assumes WGS84 when unset.
+    pub fn input_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.inner.input_coord(coord_system);
+        self
+    }
+
+    /// Coordinate system of the region coordinates returned by
+    /// [`get_region`](Self::get_region). Kakao answers in WGS84 when unset.
+    pub fn output_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.inner.output_coord(coord_system);
+        self
+    }
+
+    pub fn get_region(&self) -> Result<Vec<Region>, Error> {
+        self.runtime.block_on(self.inner.get_region())
+    }
+
+    pub fn get_address(&self) -> Result<Vec<Address>, Error> {
+        self.runtime.block_on(self.inner.get_address())
+    }
+}
+
+impl TransCoordRequest<'static> {
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn new(app_key: &str, x: f64, y: f64) -> Self {
+        TransCoordRequest {
+            inner: crate::TransCoordRequest::new(app_key, x, y),
+            runtime: new_runtime(),
+        }
+    }
+}
+
+impl<'a> TransCoordRequest<'a> {
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.inner.base_url(base_url);
+        self
+    }
+
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.inner.no_cache();
+        self
+    }
+
+    pub fn input_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.inner.input_coord(coord_system);
+        self
+    }
+
+    pub fn output_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.inner.output_coord(coord_system);
+        self
+    }
+
+    pub fn get(&self) -> Result<Vec<TransCoord>, Error> {
+        self.runtime.block_on(self.inner.get())
+    }
+}
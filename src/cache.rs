@@ -0,0 +1,274 @@
+//! Caching of API responses.
+//!
+//! A [`ResponseCache`] set on a client answers repeated requests from a [`CacheStore`]: in memory
+//! with [`MemoryStore`], or on disk with [`FileStore`]. Stores keep the raw JSON body of each
+//! response, so entries written by an older version of the crate are parsed into the current
+//! result types.
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! use {daummap::cache::{FileStore, ResponseCache}, std::time::Duration};
+//!
+//! let mut cache = ResponseCache::with_store(FileStore::new("kakao-cache").unwrap());
+//! cache
+//!     .ttl(Duration::from_secs(90 * 24 * 60 * 60))
+//!     .refresh_if_older_than(Duration::from_secs(30 * 24 * 60 * 60));
+//! let client = daummap::KakaoClient::builder(APP_KEY)
+//!     .cache(cache)
+//!     .build()
+//!     .unwrap();
+//! # }
+//! ```
+
+mod file;
+
+pub use self::file::FileStore;
+
+use {
+    lru::LruCache,
+    std::{
+        fmt, io,
+        num::NonZeroUsize,
+        sync::{Arc, Mutex},
+        time::{Duration, SystemTime},
+    },
+};
+
+/// Storage of the responses of a [`ResponseCache`].
+///
+/// Methods are called from async code without being awaited, so they should not block for long.
+/// Errors are treated as misses and never fail a request.
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &CacheKey) -> io::Result<Option<CachedResponse>>;
+
+    fn put(&self, key: &CacheKey, response: &CachedResponse) -> io::Result<()>;
+
+    fn remove(&self, key: &CacheKey) -> io::Result<()>;
+
+    fn clear(&self) -> io::Result<()>;
+
+    /// Number of responses held.
+    fn len(&self) -> io::Result<usize>;
+
+    fn is_empty(&self) -> io::Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+}
+
+/// A normalized request: the base URL without its trailing slash, the API path without its
+/// leading slash and the query parameters, sorted by name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheKey {
+    pub base_url: String,
+    pub path: String,
+    pub params: Vec<(String, String)>,
+}
+
+/// A raw Kakao response and when it was received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CachedResponse {
+    pub body: String,
+    pub stored_at: SystemTime,
+}
+
+/// Response cache of a client.
+///
+/// Responses are keyed by [`CacheKey`], so the same search sent through any request builder to the
+/// same base URL is answered from the cache while it is fresh. Only successful responses are cached. A cached
+/// response neither takes a rate limiter token nor counts towards the daily usage.
+///
+/// Clones share the same store and stats. Caching is skipped for a single request with
+/// `no_cache`.
+#[derive(Clone)]
+pub struct ResponseCache {
+    store: Arc<dyn CacheStore>,
+    ttl: Option<Duration>,
+    refresh_after: Option<Duration>,
+    offline: bool,
+    stats: Arc<Mutex<(u64, u64)>>,
+}
+
+/// Hit and miss counts of a [`ResponseCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    pub hits: u64,
+    /// Requests not found in the cache, or found expired or due for a refresh.
+    pub misses: u64,
+    /// Responses currently held, including expired ones not looked up since.
+    pub entries: usize,
+}
+
+/// In-memory LRU store.
+#[derive(Debug)]
+pub struct MemoryStore {
+    entries: Mutex<LruCache<CacheKey, CachedResponse>>,
+}
+
+pub(crate) enum Lookup {
+    Fresh(String),
+    /// Due for a refresh, but usable if the refresh fails.
+    Stale(String),
+    Missing,
+}
+
+impl ResponseCache {
+    /// A cache holding at most `capacity` responses in memory, which never expire.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_store(MemoryStore::new(capacity))
+    }
+
+    pub fn with_store(store: impl CacheStore + 'static) -> Self {
+        ResponseCache {
+            store: Arc::new(store),
+            ttl: None,
+            refresh_after: None,
+            offline: false,
+            stats: Arc::new(Mutex::new((0, 0))),
+        }
+    }
+
+    /// How long a response is kept. Older responses are dropped and requested again.
+    pub fn ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Requests responses older than `age` again, but keeps answering with them while Kakao
+    /// cannot be reached, fails with a server error or throttles requests. Other errors, like an
+    /// invalid app key, are returned.
+    pub fn refresh_if_older_than(&mut self, age: Duration) -> &mut Self {
+        self.refresh_after = Some(age);
+        self
+    }
+
+    /// Answers only from the cache, regardless of the age of the responses. Requests not found
+    /// fail with [`Error::CacheMiss`](crate::Error::CacheMiss).
+    pub fn offline(&mut self, offline: bool) -> &mut Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let (hits, misses) = *self.stats.lock().unwrap();
+        CacheStats {
+            hits,
+            misses,
+            entries: self.store.len().unwrap_or_default(),
+        }
+    }
+
+    /// Drops every response. The stats are kept.
+    pub fn clear(&self) -> io::Result<()> {
+        self.store.clear()
+    }
+
+    pub(crate) fn lookup(&self, base_url: &str, path: &str, params: &[(&str, String)]) -> Lookup {
+        let key = CacheKey::new(base_url, path, params);
+        let lookup = match self.store.get(&key).ok().flatten() {
+            None => Lookup::Missing,
+            Some(cached) if self.offline => Lookup::Fresh(cached.body),
+            Some(cached) => {
+                let age = SystemTime::now()
+                    .duration_since(cached.stored_at)
+                    .unwrap_or_default();
+                if self.ttl.is_some_and(|ttl| age >= ttl) {
+                    let _ = self.store.remove(&key);
+                    Lookup::Missing
+                } else if self.refresh_after.is_some_and(|refresh| age >= refresh) {
+                    Lookup::Stale(cached.body)
+                } else {
+                    Lookup::Fresh(cached.body)
+                }
+            }
+        };
+
+        let mut stats = self.stats.lock().unwrap();
+        match lookup {
+            Lookup::Fresh(_) => stats.0 += 1,
+            _ => stats.1 += 1,
+        }
+        lookup
+    }
+
+    pub(crate) fn insert(&self, base_url: &str, path: &str, params: &[(&str, String)], body: &str) {
+        let response = CachedResponse {
+            body: body.to_string(),
+            stored_at: SystemTime::now(),
+        };
+        let _ = self
+            .store
+            .put(&CacheKey::new(base_url, path, params), &response);
+    }
+}
+
+impl fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("ttl", &self.ttl)
+            .field("refresh_after", &self.refresh_after)
+            .field("offline", &self.offline)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl CacheKey {
+    pub fn new(base_url: &str, path: &str, params: &[(&str, String)]) -> Self {
+        let mut params = params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect::<Vec<_>>();
+        params.sort();
+        CacheKey {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            path: path.trim_start_matches('/').to_string(),
+            params,
+        }
+    }
+}
+
+impl MemoryStore {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        MemoryStore {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &CacheKey) -> io::Result<Option<CachedResponse>> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn put(&self, key: &CacheKey, response: &CachedResponse) -> io::Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .put(key.clone(), response.clone());
+        Ok(())
+    }
+
+    fn remove(&self, key: &CacheKey) -> io::Result<()> {
+        self.entries.lock().unwrap().pop(key);
+        Ok(())
+    }
+
+    fn clear(&self) -> io::Result<()> {
+        self.entries.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn len(&self) -> io::Result<usize> {
+        Ok(self.entries.lock().unwrap().len())
+    }
+}
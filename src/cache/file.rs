@@ -0,0 +1,143 @@
+use {
+    super::{CacheKey, CacheStore, CachedResponse},
+    serde::{Deserialize, Serialize},
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+        process,
+        sync::atomic::{AtomicU64, Ordering},
+        time::{Duration, UNIX_EPOCH},
+    },
+};
+
+/// Number of entries written by this process, naming their temporary files.
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// On-disk store keeping each response in a JSON file of a directory.
+///
+/// Files hold the normalized request, including the base URL it was sent to, the raw response body and the time it was received, and
+/// are named after a hash of the request. Entries are only dropped by a TTL or
+/// [`clear`](CacheStore::clear).
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    #[serde(default)]
+    base_url: String,
+    path: String,
+    params: Vec<(String, String)>,
+    body: String,
+    /// Milliseconds since the Unix epoch.
+    stored_at: u64,
+}
+
+impl FileStore {
+    /// Stores responses in `dir`, which is created if missing.
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(FileStore { dir })
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", hash(key)))
+    }
+
+    fn entries(&self) -> io::Result<impl Iterator<Item = PathBuf>> {
+        Ok(fs::read_dir(&self.dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            }))
+    }
+}
+
+impl CacheStore for FileStore {
+    fn get(&self, key: &CacheKey) -> io::Result<Option<CachedResponse>> {
+        let bytes = match fs::read(self.entry_path(key)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let entry = serde_json::from_slice::<Entry>(&bytes)?;
+        // A hash collision leaves the file of another request.
+        if entry.base_url != key.base_url || entry.path != key.path || entry.params != key.params {
+            return Ok(None);
+        }
+        Ok(Some(CachedResponse {
+            body: entry.body,
+            stored_at: UNIX_EPOCH + Duration::from_millis(entry.stored_at),
+        }))
+    }
+
+    fn put(&self, key: &CacheKey, response: &CachedResponse) -> io::Result<()> {
+        let entry = Entry {
+            base_url: key.base_url.clone(),
+            path: key.path.clone(),
+            params: key.params.clone(),
+            body: response.body.clone(),
+            stored_at: response
+                .stored_at
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        };
+        let path = self.entry_path(key);
+        // Written aside and renamed, so a crash never leaves a truncated entry. Each write has its
+        // own file, so concurrent writers of the same entry, in this process or another, never
+        // mix their bytes.
+        let tmp = path.with_extension(format!(
+            "{}.{}.tmp",
+            process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written =
+            fs::write(&tmp, serde_json::to_vec(&entry)?).and_then(|_| fs::rename(&tmp, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        written
+    }
+
+    fn remove(&self, key: &CacheKey) -> io::Result<()> {
+        match fs::remove_file(self.entry_path(key)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn clear(&self) -> io::Result<()> {
+        for path in self.entries()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> io::Result<usize> {
+        Ok(self.entries()?.count())
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the std hashers is stable between releases and runs.
+fn hash(key: &CacheKey) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let params = key
+        .params
+        .iter()
+        .flat_map(|(name, value)| vec![name.as_bytes(), b"=", value.as_bytes(), b"&"]);
+    for bytes in vec![key.base_url.as_bytes(), b"/", key.path.as_bytes(), b"?"]
+        .into_iter()
+        .chain(params)
+    {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
@@ -1,8 +1,13 @@
 use {
-    crate::{request, Meta, Place, Sort, KAKAO_LOCAL_API_BASE_URL},
-    failure::{Fail, Fallible},
+    crate::{
+        paginate::{paginate, Page},
+        proj::{self, Projection},
+        request, Documents, Error, KakaoClient, Meta, Place, Sort,
+    },
+    futures::{Stream, TryFutureExt},
     serde::Deserialize,
-    std::str::FromStr,
+    std::{borrow::Cow, str::FromStr},
+    thiserror::Error as ThisError,
 };
 
 #[derive(Debug, Clone)]
@@ -28,9 +33,9 @@ pub enum CategoryGroup {
 }
 
 impl FromStr for CategoryGroup {
-    type Err = failure::Error;
+    type Err = ParseCategoryGroup;
 
-    fn from_str(s: &str) -> Fallible<Self> {
+    fn from_str(s: &str) -> Result<Self, ParseCategoryGroup> {
         use crate::CategoryGroup::*;
 
         Ok(match s {
@@ -52,7 +57,7 @@ impl FromStr for CategoryGroup {
             "CE7" => Cafe,
             "HP8" => Hospital,
             "PM9" => Pharmacy,
-            _ => return Err(ParseCategoryGroup(s.to_string()).into()),
+            _ => return Err(ParseCategoryGroup(s.to_string())),
         })
     }
 }
@@ -83,11 +88,28 @@ impl CategoryGroup {
     }
 }
 
-#[derive(Debug, Fail)]
-#[fail(display = "Cannot parse category group from {}", _0)]
+/// Serialized as its Kakao code, e.g. `"FD6"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CategoryGroup {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_code())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CategoryGroup {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, ThisError)]
+#[error("Cannot parse category group from {0}")]
 pub struct ParseCategoryGroup(pub String);
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CategoryResponse {
     pub places: Vec<Place>,
     pub total_count: usize,
@@ -96,65 +118,125 @@ pub struct CategoryResponse {
 }
 
 #[derive(Debug, Clone)]
-pub struct CategoryRequest {
-    base_url: String,
-    app_key: String,
+pub struct CategoryRequest<'a> {
+    client: Cow<'a, KakaoClient>,
     category_group: CategoryGroup,
-    longitude: Option<f32>,
-    latitude: Option<f32>,
+    longitude: Option<f64>,
+    latitude: Option<f64>,
     radius: Option<usize>,
-    rect: Option<(f32, f32, f32, f32)>,
+    rect: Option<(f64, f64, f64, f64)>,
     page: usize,
     size: usize,
     sort: Sort,
+    prefetch: usize,
 }
 
-impl CategoryRequest {
+impl CategoryRequest<'static> {
     pub fn circle(
         app_key: &str,
         category_group: CategoryGroup,
-        longitude: f32,
-        latitude: f32,
+        longitude: f64,
+        latitude: f64,
         radius: usize,
     ) -> Self {
+        CategoryRequest::circle_with_client(
+            Cow::Owned(KakaoClient::new(app_key)),
+            category_group,
+            longitude,
+            latitude,
+            radius,
+        )
+    }
+
+    pub fn rect(
+        app_key: &str,
+        category_group: CategoryGroup,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Self {
+        CategoryRequest::rect_with_client(
+            Cow::Owned(KakaoClient::new(app_key)),
+            category_group,
+            x1,
+            y1,
+            x2,
+            y2,
+        )
+    }
+
+    /// Like [`rect`](Self::rect), with corners in another system. The rectangle is converted to
+    /// the WGS84 box enclosing it.
+    pub fn rect_projected(
+        app_key: &str,
+        category_group: CategoryGroup,
+        projection: Projection,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Self {
+        let (x1, y1, x2, y2) = proj::to_wgs84_rect(projection, x1, y1, x2, y2);
+        CategoryRequest::rect(app_key, category_group, x1, y1, x2, y2)
+    }
+}
+
+impl<'a> CategoryRequest<'a> {
+    pub(crate) fn circle_with_client(
+        client: Cow<'a, KakaoClient>,
+        category_group: CategoryGroup,
+        longitude: f64,
+        latitude: f64,
+        radius: usize,
+    ) -> Self {
+        let (size, sort) = (client.size, client.sort);
         CategoryRequest {
-            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
-            app_key: app_key.to_string(),
+            client,
             category_group,
             longitude: Some(longitude),
             latitude: Some(latitude),
             radius: Some(radius),
             rect: None,
             page: 1,
-            size: 15,
-            sort: Sort::Accuracy,
+            size,
+            sort,
+            prefetch: 0,
         }
     }
 
-    pub fn rect(
-        app_key: &str,
+    pub(crate) fn rect_with_client(
+        client: Cow<'a, KakaoClient>,
         category_group: CategoryGroup,
-        x1: f32,
-        y1: f32,
-        x2: f32,
-        y2: f32,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
     ) -> Self {
+        let (size, sort) = (client.size, client.sort);
         CategoryRequest {
-            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
-            app_key: app_key.to_string(),
+            client,
             category_group,
             longitude: None,
             latitude: None,
             radius: None,
             rect: Some((x1, y1, x2, y2)),
             page: 1,
-            size: 15,
-            sort: Sort::Accuracy,
+            size,
+            sort,
+            prefetch: 0,
         }
     }
 
     pub fn base_url(&mut self, base_url: &str) -> &mut Self {
-        self.base_url = base_url.to_string();
+        self.client.to_mut().base_url = base_url.to_string();
+        self
+    }
+
+    /// Sends the request even if the client's cache holds a response, and leaves the cache as
+    /// is.
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.client.to_mut().cache = None;
         self
     }
 
@@ -173,15 +255,33 @@ impl CategoryRequest {
         self
     }
 
-    pub async fn get(&self) -> Result<CategoryResponse, failure::Error> {
-        static API_PATH: &'static str = "/search/category.json";
+    /// Requests following pages concurrently while the stream is consumed. At most `pages` pages
+    /// beyond the current one are in flight; `0` (the default) fetches one page at a time.
+    pub fn prefetch(&mut self, pages: usize) -> &mut Self {
+        self.prefetch = pages;
+        self
+    }
+
+    pub async fn get(&self) -> Result<CategoryResponse, Error> {
+        self.get_page(self.page).await
+    }
+
+    /// Streams the places of every page, starting from the page set with [`page`](Self::page).
+    pub fn stream(&self) -> impl Stream<Item = Result<Place, Error>> + '_ {
+        paginate(self.page, self.size, self.prefetch, move |page| {
+            self.get_page(page).map_ok(Into::into)
+        })
+    }
+
+    async fn get_page(&self, page: usize) -> Result<CategoryResponse, Error> {
+        static API_PATH: &str = "/search/category.json";
 
         let mut params = vec![
             (
                 "category_group_code",
                 self.category_group.to_code().to_string(),
             ),
-            ("page", self.page.to_string()),
+            ("page", page.to_string()),
             ("size", self.size.to_string()),
             ("sort", self.sort.to_string()),
         ];
@@ -199,7 +299,7 @@ impl CategoryRequest {
             params.push(("rect", format!("{},{},{},{}", x1, y1, x2, y2)));
         }
 
-        let resp = request::<RawResponse>(&self.base_url, API_PATH, &params, &self.app_key).await?;
+        let resp = request::<RawResponse>(&self.client, API_PATH, &params).await?;
         let places = resp.documents.into_iter().map(Into::into).collect();
 
         Ok(CategoryResponse {
@@ -211,12 +311,32 @@ impl CategoryRequest {
     }
 }
 
+impl From<CategoryResponse> for Page<Place> {
+    fn from(resp: CategoryResponse) -> Self {
+        Page {
+            items: resp.places,
+            pageable_count: resp.pageable_count,
+            is_end: resp.is_end,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RawResponse {
     documents: Vec<RawPlace>,
     meta: Meta,
 }
 
+impl Documents for RawResponse {
+    fn document_count(&self) -> Option<usize> {
+        Some(self.documents.len())
+    }
+
+    fn total_count(&self) -> Option<usize> {
+        Some(self.meta.total_count)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RawPlace {
     id: String,
@@ -232,20 +352,20 @@ struct RawPlace {
     distance: String,
 }
 
-impl Into<Place> for RawPlace {
-    fn into(self) -> Place {
+impl From<RawPlace> for Place {
+    fn from(rplace: RawPlace) -> Self {
         Place {
-            id: self.id.parse::<usize>().ok(),
-            name: self.place_name,
-            category: self.category_name,
-            category_group: self.category_group_code.parse::<CategoryGroup>().ok(),
-            phone: self.phone,
-            address: self.address_name,
-            road_address: self.road_address_name,
-            longitude: self.x.parse::<f32>().ok(),
-            latitude: self.y.parse::<f32>().ok(),
-            url: self.place_url,
-            distance: self.distance.parse::<usize>().ok(),
+            id: rplace.id.parse::<usize>().ok(),
+            name: rplace.place_name,
+            category: rplace.category_name,
+            category_group: rplace.category_group_code.parse::<CategoryGroup>().ok(),
+            phone: rplace.phone,
+            address: rplace.address_name,
+            road_address: rplace.road_address_name,
+            longitude: rplace.x.parse::<f64>().ok(),
+            latitude: rplace.y.parse::<f64>().ok(),
+            url: rplace.place_url,
+            distance: rplace.distance.parse::<usize>().ok(),
         }
     }
 }
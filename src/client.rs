@@ -0,0 +1,273 @@
+use {
+    crate::{
+        layer::Layer,
+        proj::{self, Projection},
+        transport::{ReqwestTransport, Transport},
+        AddressRequest, CategoryGroup, CategoryRequest, CoordRequest, Error, KeywordRequest,
+        RateLimiter, ResponseCache, RetryPolicy, Sort, TransCoordRequest, KAKAO_LOCAL_API_BASE_URL,
+    },
+    reqwest::Client,
+    std::{borrow::Cow, sync::Arc, time::Duration},
+};
+
+/// A reusable handle to the Kakao Local API.
+///
+/// Holds the app key, the base URL, a pooled HTTP transport and the defaults used by the request
+/// builders it creates. Cloning is cheap and clones share the same connection pool, so a single
+/// client should be created once and shared.
+#[derive(Debug, Clone)]
+pub struct KakaoClient {
+    pub(crate) app_key: String,
+    pub(crate) base_url: String,
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) layers: Vec<Arc<dyn Layer>>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) cache: Option<ResponseCache>,
+    pub(crate) size: usize,
+    pub(crate) sort: Sort,
+    #[cfg(feature = "tracing")]
+    pub(crate) trace_queries: bool,
+}
+
+impl KakaoClient {
+    pub fn new(app_key: &str) -> Self {
+        KakaoClient {
+            app_key: app_key.to_string(),
+            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
+            transport: Arc::new(ReqwestTransport::default()),
+            layers: Vec::new(),
+            retry: RetryPolicy::none(),
+            rate_limiter: None,
+            cache: None,
+            size: 15,
+            sort: Sort::Accuracy,
+            #[cfg(feature = "tracing")]
+            trace_queries: false,
+        }
+    }
+
+    pub fn builder(app_key: &str) -> KakaoClientBuilder {
+        KakaoClientBuilder::new(app_key)
+    }
+
+    pub fn app_key(&self) -> &str {
+        &self.app_key
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    pub fn address(&self, query: &str) -> AddressRequest<'_> {
+        AddressRequest::with_client(Cow::Borrowed(self), query)
+    }
+
+    pub fn keyword(&self, query: &str) -> KeywordRequest<'_> {
+        KeywordRequest::with_client(Cow::Borrowed(self), query)
+    }
+
+    pub fn category_circle(
+        &self,
+        category_group: CategoryGroup,
+        longitude: f64,
+        latitude: f64,
+        radius: usize,
+    ) -> CategoryRequest<'_> {
+        CategoryRequest::circle_with_client(
+            Cow::Borrowed(self),
+            category_group,
+            longitude,
+            latitude,
+            radius,
+        )
+    }
+
+    pub fn category_rect(
+        &self,
+        category_group: CategoryGroup,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> CategoryRequest<'_> {
+        CategoryRequest::rect_with_client(Cow::Borrowed(self), category_group, x1, y1, x2, y2)
+    }
+
+    /// Like [`category_rect`](Self::category_rect), with corners in another system. The
+    /// rectangle is converted to the WGS84 box enclosing it.
+    pub fn category_rect_projected(
+        &self,
+        category_group: CategoryGroup,
+        projection: Projection,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> CategoryRequest<'_> {
+        let (x1, y1, x2, y2) = proj::to_wgs84_rect(projection, x1, y1, x2, y2);
+        self.category_rect(category_group, x1, y1, x2, y2)
+    }
+
+    pub fn coord(&self, longitude: f64, latitude: f64) -> CoordRequest<'_> {
+        CoordRequest::with_client(Cow::Borrowed(self), longitude, latitude)
+    }
+
+    /// Like [`coord`](Self::coord), with a coordinate in another system converted to WGS84
+    /// locally.
+    pub fn coord_projected(&self, projection: Projection, x: f64, y: f64) -> CoordRequest<'_> {
+        let (longitude, latitude) = projection.to_wgs84(x, y);
+        self.coord(longitude, latitude)
+    }
+
+    pub fn transcoord(&self, x: f64, y: f64) -> TransCoordRequest<'_> {
+        TransCoordRequest::with_client(Cow::Borrowed(self), x, y)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KakaoClientBuilder {
+    app_key: String,
+    base_url: String,
+    transport: Option<Arc<dyn Transport>>,
+    layers: Vec<Arc<dyn Layer>>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    retry: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<ResponseCache>,
+    size: usize,
+    sort: Sort,
+    #[cfg(feature = "tracing")]
+    trace_queries: bool,
+}
+
+impl KakaoClientBuilder {
+    pub fn new(app_key: &str) -> Self {
+        KakaoClientBuilder {
+            app_key: app_key.to_string(),
+            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
+            transport: None,
+            layers: Vec::new(),
+            timeout: None,
+            user_agent: None,
+            retry: RetryPolicy::none(),
+            rate_limiter: None,
+            cache: None,
+            size: 15,
+            sort: Sort::Accuracy,
+            #[cfg(feature = "tracing")]
+            trace_queries: false,
+        }
+    }
+
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Use an already configured `reqwest::Client`. `timeout` and `user_agent` are ignored when
+    /// this is set.
+    pub fn http_client(&mut self, client: Client) -> &mut Self {
+        self.transport(ReqwestTransport::new(client))
+    }
+
+    /// Send requests through `transport` instead of reqwest. `timeout` and `user_agent` are
+    /// ignored when this is set.
+    pub fn transport(&mut self, transport: impl Transport + 'static) -> &mut Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Add a layer called around every request sent through the client. Layers see requests in
+    /// the order they were added and responses in reverse order.
+    pub fn layer(&mut self, layer: impl Layer + 'static) -> &mut Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent(&mut self, user_agent: &str) -> &mut Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Retry policy applied to every request sent through the client. No request is retried by
+    /// default.
+    pub fn retry(&mut self, retry: RetryPolicy) -> &mut Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Rate limiter applied to every request sent through the client. Pass a clone of the same
+    /// limiter to every client sharing an app key.
+    pub fn rate_limiter(&mut self, rate_limiter: RateLimiter) -> &mut Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Response cache consulted by every request sent through the client. Pass a clone of the
+    /// same cache to share it between clients.
+    pub fn cache(&mut self, cache: ResponseCache) -> &mut Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Default page size of the request builders created by the client.
+    pub fn size(&mut self, size: usize) -> &mut Self {
+        self.size = size;
+        self
+    }
+
+    /// Default sort order of the keyword and category request builders created by the client.
+    pub fn sort(&mut self, sort: Sort) -> &mut Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Record the query text of address and keyword searches in `tracing` spans. Off by default,
+    /// as queries can hold personal data.
+    #[cfg(feature = "tracing")]
+    pub fn trace_queries(&mut self, trace_queries: bool) -> &mut Self {
+        self.trace_queries = trace_queries;
+        self
+    }
+
+    pub fn build(&self) -> Result<KakaoClient, Error> {
+        let transport = match self.transport {
+            Some(ref transport) => transport.clone(),
+            None => {
+                let mut builder = Client::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(ref user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent.as_str());
+                }
+                Arc::new(ReqwestTransport::new(builder.build()?))
+            }
+        };
+
+        Ok(KakaoClient {
+            app_key: self.app_key.clone(),
+            base_url: self.base_url.clone(),
+            transport,
+            layers: self.layers.clone(),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            cache: self.cache.clone(),
+            size: self.size,
+            sort: self.sort,
+            #[cfg(feature = "tracing")]
+            trace_queries: self.trace_queries,
+        })
+    }
+}
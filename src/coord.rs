@@ -1,30 +1,57 @@
 use {
-    crate::{request, Address, LandLotAddress, RoadAddress, KAKAO_LOCAL_API_BASE_URL},
+    crate::{
+        proj::Projection, request, Address, CoordSystem, Documents, Error, KakaoClient,
+        LandLotAddress, RoadAddress,
+    },
     serde::{de::DeserializeOwned, Deserialize},
+    std::borrow::Cow,
 };
 
 #[derive(Debug, Clone)]
-pub struct CoordRequest {
-    base_url: String,
-    app_key: String,
+pub struct CoordRequest<'a> {
+    client: Cow<'a, KakaoClient>,
     page: usize,
-    longitude: f32,
-    latitude: f32,
+    longitude: f64,
+    latitude: f64,
+    input_coord: Option<CoordSystem>,
+    output_coord: Option<CoordSystem>,
 }
 
-impl CoordRequest {
-    pub fn new(app_key: &str, longitude: f32, latitude: f32) -> Self {
+impl CoordRequest<'static> {
+    /// With [`input_coord`](CoordRequest::input_coord) set, `longitude` and `latitude` are the x
+    /// and y of that coordinate system.
+    pub fn new(app_key: &str, longitude: f64, latitude: f64) -> Self {
+        CoordRequest::with_client(Cow::Owned(KakaoClient::new(app_key)), longitude, latitude)
+    }
+
+    /// Like [`new`](Self::new), with a coordinate in another system converted to WGS84 locally.
+    pub fn projected(app_key: &str, projection: Projection, x: f64, y: f64) -> Self {
+        let (longitude, latitude) = projection.to_wgs84(x, y);
+        CoordRequest::new(app_key, longitude, latitude)
+    }
+}
+
+impl<'a> CoordRequest<'a> {
+    pub(crate) fn with_client(client: Cow<'a, KakaoClient>, longitude: f64, latitude: f64) -> Self {
         CoordRequest {
-            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
-            app_key: app_key.to_string(),
+            client,
             page: 1,
             longitude,
             latitude,
+            input_coord: None,
+            output_coord: None,
         }
     }
 
     pub fn base_url(&mut self, base_url: &str) -> &mut Self {
-        self.base_url = base_url.to_string();
+        self.client.to_mut().base_url = base_url.to_string();
+        self
+    }
+
+    /// Sends the request even if the client's cache holds a response, and leaves the cache as
+    /// is.
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.client.to_mut().cache = None;
         self
     }
 
@@ -33,36 +60,69 @@ impl CoordRequest {
         self
     }
 
-    async fn request<T: DeserializeOwned>(&self, api_path: &str) -> Result<T, failure::Error> {
-        request::<T>(
-            &self.base_url,
-            api_path,
-            &[
-                ("page", self.page.to_string()),
-                ("x", self.longitude.to_string()),
-                ("y", self.latitude.to_string()),
-            ],
-            &self.app_key,
-        )
-        .await
+    /// Coordinate system of the given coordinate. Kakao assumes WGS84 when unset.
+    pub fn input_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.input_coord = Some(coord_system);
+        self
+    }
+
+    /// Coordinate system of the region coordinates returned by
+    /// [`get_region`](Self::get_region). Kakao answers in WGS84 when unset. Ignored by
+    /// [`get_address`](Self::get_address), which returns no coordinates.
+    pub fn output_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.output_coord = Some(coord_system);
+        self
+    }
+
+    async fn request<T: DeserializeOwned + Documents>(
+        &self,
+        api_path: &str,
+        output_coord: Option<CoordSystem>,
+    ) -> Result<T, Error> {
+        let mut params = vec![
+            ("page", self.page.to_string()),
+            ("x", self.longitude.to_string()),
+            ("y", self.latitude.to_string()),
+        ];
+
+        if let Some(input_coord) = self.input_coord {
+            params.push(("input_coord", input_coord.to_string()));
+        }
+        if let Some(output_coord) = output_coord {
+            params.push(("output_coord", output_coord.to_string()));
+        }
+
+        request::<T>(&self.client, api_path, &params).await
     }
 
-    pub async fn get_region(&self) -> Result<Vec<Region>, failure::Error> {
-        static API_PATH: &'static str = "/geo/coord2regioncode.json";
+    pub async fn get_region(&self) -> Result<Vec<Region>, Error> {
+        static API_PATH: &str = "/geo/coord2regioncode.json";
 
-        let resp = self.request::<Coord2RegionResponse>(API_PATH).await?;
-        Ok(resp.documents.into_iter().map(Into::into).collect())
+        let resp = self
+            .request::<Coord2RegionResponse>(API_PATH, self.output_coord)
+            .await?;
+        let coord_system = self.output_coord.unwrap_or(CoordSystem::Wgs84);
+        Ok(resp
+            .documents
+            .into_iter()
+            .map(|rreg| rreg.into_region(coord_system))
+            .collect())
     }
 
-    pub async fn get_address(&self) -> Result<Vec<Address>, failure::Error> {
-        static API_PATH: &'static str = "/geo/coord2address.json";
+    pub async fn get_address(&self) -> Result<Vec<Address>, Error> {
+        static API_PATH: &str = "/geo/coord2address.json";
 
-        let resp = self.request::<Coord2AddressResponse>(API_PATH).await?;
+        let resp = self
+            .request::<Coord2AddressResponse>(API_PATH, None)
+            .await?;
         Ok(resp
             .documents
             .into_iter()
             .map(|document| Address {
                 address: None,
+                address_type: None,
+                longitude: None,
+                latitude: None,
                 land_lot: document.address.map(Into::into),
                 road: document.road_address.map(Into::into),
             })
@@ -75,11 +135,23 @@ struct Coord2AddressResponse {
     documents: Vec<Coord2AddressDocument>,
 }
 
+impl Documents for Coord2AddressResponse {
+    fn document_count(&self) -> Option<usize> {
+        Some(self.documents.len())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct Coord2RegionResponse {
     documents: Vec<RawRegion>,
 }
 
+impl Documents for Coord2RegionResponse {
+    fn document_count(&self) -> Option<usize> {
+        Some(self.documents.len())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct Coord2AddressDocument {
     address: Option<RawLandLotAddress>,
@@ -88,26 +160,51 @@ struct Coord2AddressDocument {
 
 #[derive(Debug, Deserialize)]
 struct RawRegion {
+    #[serde(default)]
+    region_type: String,
     address_name: String,
     region_1depth_name: String,
     region_2depth_name: String,
     region_3depth_name: String,
     region_4depth_name: String,
     code: String,
-    x: f32,
-    y: f32,
+    x: f64,
+    y: f64,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Region {
+    pub region_type: Option<RegionType>,
     pub address: String,
     pub province: String,
     pub city: String,
     pub town: String,
     pub neighborhood: String,
     pub code: Option<usize>,
-    pub longitude: Option<f32>,
-    pub latitude: Option<f32>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    /// Coordinate system of `longitude` and `latitude`.
+    pub coord_system: CoordSystem,
+}
+
+/// Kakao returns both kinds of region for a coordinate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegionType {
+    /// Administrative region (행정동), `H`.
+    Administrative,
+    /// Legal region (법정동), `B`.
+    Legal,
+}
+
+impl RegionType {
+    pub fn to_code<'a>(self) -> &'a str {
+        match self {
+            RegionType::Administrative => "H",
+            RegionType::Legal => "B",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -136,58 +233,64 @@ struct RawRoadAddress {
     zone_no: String,
 }
 
-impl From<RawRegion> for Region {
-    fn from(rreg: RawRegion) -> Self {
+impl RawRegion {
+    fn into_region(self, coord_system: CoordSystem) -> Region {
         Region {
-            address: rreg.address_name,
-            province: rreg.region_1depth_name,
-            city: rreg.region_2depth_name,
-            town: rreg.region_3depth_name,
-            neighborhood: rreg.region_4depth_name,
-            code: rreg.code.parse::<usize>().ok(),
-            longitude: Some(rreg.x),
-            latitude: Some(rreg.y),
+            region_type: match self.region_type.as_str() {
+                "H" => Some(RegionType::Administrative),
+                "B" => Some(RegionType::Legal),
+                _ => None,
+            },
+            address: self.address_name,
+            province: self.region_1depth_name,
+            city: self.region_2depth_name,
+            town: self.region_3depth_name,
+            neighborhood: self.region_4depth_name,
+            code: self.code.parse::<usize>().ok(),
+            longitude: Some(self.x),
+            latitude: Some(self.y),
+            coord_system,
         }
     }
 }
 
-impl Into<LandLotAddress> for RawLandLotAddress {
-    fn into(self) -> LandLotAddress {
+impl From<RawLandLotAddress> for LandLotAddress {
+    fn from(raddr: RawLandLotAddress) -> Self {
         LandLotAddress {
-            address: self.address_name,
-            province: self.region_1depth_name,
-            city: self.region_2depth_name,
-            town: self.region_3depth_name,
+            address: raddr.address_name,
+            province: raddr.region_1depth_name,
+            city: raddr.region_2depth_name,
+            town: raddr.region_3depth_name,
             neighborhood: None,
             h_code: None,
             b_code: None,
-            is_mountain: if self.mountain_yn.is_empty() {
+            is_mountain: if raddr.mountain_yn.is_empty() {
                 None
             } else {
-                Some(self.mountain_yn == "Y")
+                Some(raddr.mountain_yn == "Y")
             },
-            main_address_number: self.main_address_no.parse::<usize>().ok(),
-            sub_address_number: self.sub_address_no.parse::<usize>().ok(),
-            zip_code: self.zip_code.parse::<usize>().ok(),
+            main_address_number: raddr.main_address_no.parse::<usize>().ok(),
+            sub_address_number: raddr.sub_address_no.parse::<usize>().ok(),
+            zip_code: raddr.zip_code.parse::<usize>().ok(),
             longitude: None,
             latitude: None,
         }
     }
 }
 
-impl Into<RoadAddress> for RawRoadAddress {
-    fn into(self) -> RoadAddress {
+impl From<RawRoadAddress> for RoadAddress {
+    fn from(raddr: RawRoadAddress) -> Self {
         RoadAddress {
-            address: self.address_name,
-            province: self.region_1depth_name,
-            city: self.region_2depth_name,
-            town: self.region_3depth_name,
-            road_name: self.road_name,
-            is_underground: self.underground_yn == "Y",
-            main_building_number: self.main_building_no.parse::<usize>().ok(),
-            sub_building_number: self.sub_building_no.parse::<usize>().ok(),
-            building_name: self.building_name,
-            post_code: self.zone_no.parse::<usize>().ok(),
+            address: raddr.address_name,
+            province: raddr.region_1depth_name,
+            city: raddr.region_2depth_name,
+            town: raddr.region_3depth_name,
+            road_name: raddr.road_name,
+            is_underground: raddr.underground_yn == "Y",
+            main_building_number: raddr.main_building_no.parse::<usize>().ok(),
+            sub_building_number: raddr.sub_building_no.parse::<usize>().ok(),
+            building_name: raddr.building_name,
+            post_code: raddr.zone_no.parse::<usize>().ok(),
             longitude: None,
             latitude: None,
         }
@@ -0,0 +1,196 @@
+use {
+    crate::{category::ParseCategoryGroup, transcoord::ParseCoordSystem},
+    reqwest::StatusCode,
+    serde::Deserialize,
+    std::{fmt, time::Duration},
+    thiserror::Error,
+};
+
+/// Maximum number of bytes of a response body kept in an error.
+const BODY_SNIPPET_LEN: usize = 512;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The request could not be sent or the response could not be read.
+    #[error("Transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// A custom [`Transport`](crate::transport::Transport) could not send the request or read
+    /// the response.
+    #[error("Transport error: {0}")]
+    Http(Box<dyn std::error::Error + Send + Sync>),
+    /// Kakao answered with a non-success status and a body that is not a Kakao error payload.
+    #[error("HTTP status {status}: {body}")]
+    Status { status: StatusCode, body: String },
+    /// Kakao answered with a non-success status and an error payload.
+    #[error(transparent)]
+    Api(#[from] KakaoApiError),
+    /// The response body does not match the expected schema.
+    #[error("Cannot deserialize response: {source} (body: {body})")]
+    Deserialize {
+        #[source]
+        source: serde_json::Error,
+        body: String,
+    },
+    #[error("Invalid URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error(transparent)]
+    ParseCategoryGroup(#[from] ParseCategoryGroup),
+    #[error(transparent)]
+    ParseCoordSystem(#[from] ParseCoordSystem),
+    /// The client-side rate limiter refused to send the request.
+    #[error("Rate limited, retry after {retry_after:?}")]
+    RateLimited {
+        retry_after: Duration,
+        /// `true` if the daily limit is used up, `false` if only the per-second rate is exceeded.
+        daily: bool,
+    },
+    /// The client's cache is offline and holds no response to the request.
+    #[error("No cached response to {path}")]
+    CacheMiss { path: String },
+    /// The request kept failing with retryable errors until the retry policy gave up.
+    #[error("Giving up after {attempts} attempts: {source}")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    pub(crate) fn from_status(status: StatusCode, body: &str) -> Self {
+        match KakaoApiError::from_body(status, body) {
+            Some(e) => Error::Api(e),
+            None => Error::Status {
+                status,
+                body: snippet(body),
+            },
+        }
+    }
+
+    pub(crate) fn deserialize(source: serde_json::Error, body: &str) -> Self {
+        Error::Deserialize {
+            source,
+            body: snippet(body),
+        }
+    }
+
+    /// HTTP status of the response, if Kakao answered at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Transport(e) => e.status(),
+            Error::Status { status, .. } => Some(*status),
+            Error::Api(e) => Some(e.status),
+            Error::RetriesExhausted { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+
+    /// The structured Kakao error payload, if Kakao sent one.
+    pub fn api_error(&self) -> Option<&KakaoApiError> {
+        match self {
+            Error::Api(e) => Some(e),
+            Error::RetriesExhausted { source, .. } => source.api_error(),
+            _ => None,
+        }
+    }
+
+    /// Number of attempts made before giving up, if the request was retried.
+    pub fn attempts(&self) -> Option<u32> {
+        match self {
+            Error::RetriesExhausted { attempts, .. } => Some(*attempts),
+            _ => None,
+        }
+    }
+}
+
+/// An error payload returned by Kakao.
+///
+/// Kakao answers in one of two shapes, `{"errorType": ..., "message": ...}` from the Local API
+/// gateway and `{"code": -401, "msg": ...}` from the common Kakao API layer. Both are folded into
+/// this struct; fields the payload did not carry are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KakaoApiError {
+    pub status: StatusCode,
+    pub error_type: Option<String>,
+    pub code: Option<i32>,
+    pub message: String,
+}
+
+impl KakaoApiError {
+    pub(crate) fn from_body(status: StatusCode, body: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct RawApiError {
+            #[serde(rename = "errorType")]
+            error_type: Option<String>,
+            message: Option<String>,
+            code: Option<i32>,
+            msg: Option<String>,
+        }
+
+        let raw = serde_json::from_str::<RawApiError>(body).ok()?;
+        if raw.error_type.is_none() && raw.code.is_none() {
+            return None;
+        }
+
+        Some(KakaoApiError {
+            status,
+            error_type: raw.error_type,
+            code: raw.code,
+            message: raw.message.or(raw.msg).unwrap_or_default(),
+        })
+    }
+
+    fn is_error_type(&self, types: &[&str]) -> bool {
+        self.error_type
+            .as_ref()
+            .map(|t| types.contains(&t.as_str()))
+            .unwrap_or(false)
+    }
+
+    /// The daily or per-second quota of the app key is used up.
+    pub fn is_quota_exceeded(&self) -> bool {
+        self.status == StatusCode::TOO_MANY_REQUESTS
+            || self.code == Some(-10)
+            || self.is_error_type(&["RequestThrottled", "LimitExceeded"])
+    }
+
+    /// The app key is missing, malformed or unknown.
+    pub fn is_invalid_key(&self) -> bool {
+        self.status == StatusCode::UNAUTHORIZED
+            || self.code == Some(-401)
+            || self.is_error_type(&["AccessDeniedError", "InvalidAccessKey"])
+    }
+
+    /// A request parameter is missing or has an invalid value.
+    pub fn is_invalid_argument(&self) -> bool {
+        self.code == Some(-2)
+            || self.is_error_type(&["InvalidArgument", "MissingParameter"])
+            || (self.status == StatusCode::BAD_REQUEST && self.code.is_none())
+    }
+}
+
+impl fmt::Display for KakaoApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Kakao API error ({})", self.status)?;
+        if let Some(ref error_type) = self.error_type {
+            write!(f, " {}", error_type)?;
+        }
+        if let Some(code) = self.code {
+            write!(f, " [{}]", code)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for KakaoApiError {}
+
+pub(crate) fn snippet(body: &str) -> String {
+    if body.len() <= BODY_SNIPPET_LEN {
+        return body.to_string();
+    }
+    let mut end = BODY_SNIPPET_LEN;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &body[..end])
+}
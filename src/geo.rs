@@ -0,0 +1,272 @@
+//! Conversions to [`geo_types`] and [`geojson`].
+//!
+//! Results convert to a [`Point`] with `TryFrom`, failing when Kakao returned no coordinate, and
+//! to GeoJSON features whose properties are the non-geometry fields. Addresses use their
+//! [`best_coordinate`](Address::best_coordinate).
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! use geo_types::{coord, Rect};
+//!
+//! let client = daummap::KakaoClient::new(APP_KEY);
+//! let resp = client
+//!     .keyword("카카오프렌즈")
+//!     .rect_bounds(Rect::new(
+//!         coord! { x: 127.0, y: 37.4 },
+//!         coord! { x: 127.1, y: 37.6 },
+//!     ))
+//!     .get()
+//!     .await
+//!     .unwrap();
+//! println!("{}", resp.to_feature_collection());
+//! # }
+//! ```
+
+use {
+    crate::{
+        proj::Projection, Address, AddressResponse, CategoryGroup, CategoryRequest,
+        CategoryResponse, CoordRequest, KakaoClient, KeywordRequest, KeywordResponse,
+        LandLotAddress, Place, Region, RoadAddress,
+    },
+    geo_types::{Point, Rect},
+    geojson::{feature::Id, Feature, FeatureCollection, Geometry, JsonObject, Value},
+    serde_json::json,
+    std::convert::TryFrom,
+    thiserror::Error as ThisError,
+};
+
+/// The result has no coordinate to convert.
+#[derive(Debug, Clone, ThisError)]
+#[error("Missing coordinate")]
+pub struct MissingCoordinate;
+
+impl TryFrom<&Place> for Point<f64> {
+    type Error = MissingCoordinate;
+
+    fn try_from(place: &Place) -> Result<Self, MissingCoordinate> {
+        point(place.longitude, place.latitude)
+    }
+}
+
+impl TryFrom<&Address> for Point<f64> {
+    type Error = MissingCoordinate;
+
+    fn try_from(addr: &Address) -> Result<Self, MissingCoordinate> {
+        let (longitude, latitude) = addr.best_coordinate().ok_or(MissingCoordinate)?;
+        Ok(Point::new(longitude, latitude))
+    }
+}
+
+impl TryFrom<&LandLotAddress> for Point<f64> {
+    type Error = MissingCoordinate;
+
+    fn try_from(addr: &LandLotAddress) -> Result<Self, MissingCoordinate> {
+        point(addr.longitude, addr.latitude)
+    }
+}
+
+impl TryFrom<&RoadAddress> for Point<f64> {
+    type Error = MissingCoordinate;
+
+    fn try_from(addr: &RoadAddress) -> Result<Self, MissingCoordinate> {
+        point(addr.longitude, addr.latitude)
+    }
+}
+
+/// The point is in the [`coord_system`](Region::coord_system) of the region.
+impl TryFrom<&Region> for Point<f64> {
+    type Error = MissingCoordinate;
+
+    fn try_from(region: &Region) -> Result<Self, MissingCoordinate> {
+        point(region.longitude, region.latitude)
+    }
+}
+
+impl Place {
+    pub fn to_geojson_feature(&self) -> Feature {
+        feature(
+            Point::try_from(self).ok(),
+            self.id.map(|id| Id::Number(id.into())),
+            json!({
+                "id": self.id,
+                "name": self.name,
+                "category": self.category,
+                "category_group": self.category_group.as_ref().map(CategoryGroup::to_code),
+                "phone": self.phone,
+                "address": self.address,
+                "road_address": self.road_address,
+                "url": self.url,
+                "distance": self.distance,
+            }),
+        )
+    }
+}
+
+impl Address {
+    pub fn to_geojson_feature(&self) -> Feature {
+        feature(
+            Point::try_from(self).ok(),
+            None,
+            json!({
+                "address": self.address,
+                "address_type": self.address_type.map(|address_type| address_type.to_code()),
+                "land_lot": self.land_lot.as_ref().map(land_lot_properties),
+                "road": self.road.as_ref().map(road_properties),
+            }),
+        )
+    }
+}
+
+impl LandLotAddress {
+    pub fn to_geojson_feature(&self) -> Feature {
+        feature(Point::try_from(self).ok(), None, land_lot_properties(self))
+    }
+}
+
+impl RoadAddress {
+    pub fn to_geojson_feature(&self) -> Feature {
+        feature(Point::try_from(self).ok(), None, road_properties(self))
+    }
+}
+
+impl Region {
+    /// A feature located in WGS84, as GeoJSON requires. Regions in another coordinate system are
+    /// converted with [`proj`](crate::proj), and have no geometry if the system has no offline
+    /// conversion. The `coord_system` property is the one Kakao answered in.
+    pub fn to_geojson_feature(&self) -> Feature {
+        let geometry = Point::try_from(self).ok().and_then(|point| {
+            let projection = Projection::try_from(self.coord_system).ok()?;
+            let (longitude, latitude) = projection.to_wgs84(point.x(), point.y());
+            Some(Point::new(longitude, latitude))
+        });
+        feature(
+            geometry,
+            self.code.map(|code| Id::String(code.to_string())),
+            json!({
+                "region_type": self.region_type.map(|region_type| region_type.to_code()),
+                "address": self.address,
+                "province": self.province,
+                "city": self.city,
+                "town": self.town,
+                "neighborhood": self.neighborhood,
+                "code": self.code,
+                "coord_system": self.coord_system.to_code(),
+            }),
+        )
+    }
+}
+
+impl KeywordResponse {
+    pub fn to_feature_collection(&self) -> FeatureCollection {
+        collection(self.places.iter().map(Place::to_geojson_feature))
+    }
+}
+
+impl CategoryResponse {
+    pub fn to_feature_collection(&self) -> FeatureCollection {
+        collection(self.places.iter().map(Place::to_geojson_feature))
+    }
+}
+
+impl AddressResponse {
+    pub fn to_feature_collection(&self) -> FeatureCollection {
+        collection(self.addresses.iter().map(Address::to_geojson_feature))
+    }
+}
+
+impl KakaoClient {
+    pub fn category_circle_point(
+        &self,
+        category_group: CategoryGroup,
+        center: Point<f64>,
+        radius: usize,
+    ) -> CategoryRequest<'_> {
+        self.category_circle(category_group, center.x(), center.y(), radius)
+    }
+
+    pub fn category_rect_bounds(
+        &self,
+        category_group: CategoryGroup,
+        rect: Rect<f64>,
+    ) -> CategoryRequest<'_> {
+        let (min, max) = (rect.min(), rect.max());
+        self.category_rect(category_group, min.x, min.y, max.x, max.y)
+    }
+
+    pub fn coord_point(&self, point: Point<f64>) -> CoordRequest<'_> {
+        self.coord(point.x(), point.y())
+    }
+}
+
+impl<'a> KeywordRequest<'a> {
+    pub fn coord_point(&mut self, point: Point<f64>) -> &mut Self {
+        self.coord(point.x(), point.y())
+    }
+
+    pub fn rect_bounds(&mut self, rect: Rect<f64>) -> &mut Self {
+        let (min, max) = (rect.min(), rect.max());
+        self.rect(min.x, min.y, max.x, max.y)
+    }
+}
+
+fn point(longitude: Option<f64>, latitude: Option<f64>) -> Result<Point<f64>, MissingCoordinate> {
+    match (longitude, latitude) {
+        (Some(longitude), Some(latitude)) => Ok(Point::new(longitude, latitude)),
+        _ => Err(MissingCoordinate),
+    }
+}
+
+fn land_lot_properties(addr: &LandLotAddress) -> serde_json::Value {
+    json!({
+        "address": addr.address,
+        "province": addr.province,
+        "city": addr.city,
+        "town": addr.town,
+        "neighborhood": addr.neighborhood,
+        "h_code": addr.h_code,
+        "b_code": addr.b_code,
+        "is_mountain": addr.is_mountain,
+        "main_address_number": addr.main_address_number,
+        "sub_address_number": addr.sub_address_number,
+        "zip_code": addr.zip_code,
+    })
+}
+
+fn road_properties(addr: &RoadAddress) -> serde_json::Value {
+    json!({
+        "address": addr.address,
+        "province": addr.province,
+        "city": addr.city,
+        "town": addr.town,
+        "road_name": addr.road_name,
+        "is_underground": addr.is_underground,
+        "main_building_number": addr.main_building_number,
+        "sub_building_number": addr.sub_building_number,
+        "building_name": addr.building_name,
+        "post_code": addr.post_code,
+    })
+}
+
+fn feature(point: Option<Point<f64>>, id: Option<Id>, properties: serde_json::Value) -> Feature {
+    let properties = match properties {
+        serde_json::Value::Object(properties) => properties,
+        _ => JsonObject::new(),
+    };
+    Feature {
+        bbox: None,
+        geometry: point.map(|point| Geometry::new(Value::Point(vec![point.x(), point.y()]))),
+        id,
+        properties: Some(properties),
+        foreign_members: None,
+    }
+}
+
+fn collection(features: impl Iterator<Item = Feature>) -> FeatureCollection {
+    FeatureCollection {
+        bbox: None,
+        features: features.collect(),
+        foreign_members: None,
+    }
+}
@@ -1,9 +1,16 @@
 use {
-    crate::{request, CategoryGroup, Meta, Sort, KAKAO_LOCAL_API_BASE_URL},
+    crate::{
+        paginate::{paginate, Page},
+        proj::{self, Projection},
+        request, CategoryGroup, Documents, Error, KakaoClient, Meta, Sort,
+    },
+    futures::{Stream, TryFutureExt},
     serde::Deserialize,
+    std::borrow::Cow,
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Place {
     pub id: Option<usize>,
     pub name: String,
@@ -12,13 +19,14 @@ pub struct Place {
     pub phone: String,
     pub address: String,
     pub road_address: String,
-    pub longitude: Option<f32>,
-    pub latitude: Option<f32>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
     pub url: String,
     pub distance: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeywordResponse {
     pub places: Vec<Place>,
     pub total_count: usize,
@@ -27,25 +35,31 @@ pub struct KeywordResponse {
 }
 
 #[derive(Debug, Clone)]
-pub struct KeywordRequest {
-    base_url: String,
-    app_key: String,
+pub struct KeywordRequest<'a> {
+    client: Cow<'a, KakaoClient>,
     query: String,
     category_group: Option<CategoryGroup>,
-    longitude: Option<f32>,
-    latitude: Option<f32>,
+    longitude: Option<f64>,
+    latitude: Option<f64>,
     radius: Option<usize>,
-    rect: Option<(f32, f32, f32, f32)>,
+    rect: Option<(f64, f64, f64, f64)>,
     page: usize,
     size: usize,
     sort: Sort,
+    prefetch: usize,
 }
 
-impl KeywordRequest {
+impl KeywordRequest<'static> {
     pub fn new(app_key: &str, query: &str) -> Self {
+        KeywordRequest::with_client(Cow::Owned(KakaoClient::new(app_key)), query)
+    }
+}
+
+impl<'a> KeywordRequest<'a> {
+    pub(crate) fn with_client(client: Cow<'a, KakaoClient>, query: &str) -> Self {
+        let (size, sort) = (client.size, client.sort);
         KeywordRequest {
-            base_url: KAKAO_LOCAL_API_BASE_URL.to_string(),
-            app_key: app_key.to_string(),
+            client,
             query: query.to_string(),
             category_group: None,
             longitude: None,
@@ -53,13 +67,21 @@ impl KeywordRequest {
             radius: None,
             rect: None,
             page: 1,
-            size: 15,
-            sort: Sort::Accuracy,
+            size,
+            sort,
+            prefetch: 0,
         }
     }
 
     pub fn base_url(&mut self, base_url: &str) -> &mut Self {
-        self.base_url = base_url.to_string();
+        self.client.to_mut().base_url = base_url.to_string();
+        self
+    }
+
+    /// Sends the request even if the client's cache holds a response, and leaves the cache as
+    /// is.
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.client.to_mut().cache = None;
         self
     }
 
@@ -68,17 +90,17 @@ impl KeywordRequest {
         self
     }
 
-    pub fn longitude(&mut self, x: f32) -> &mut Self {
+    pub fn longitude(&mut self, x: f64) -> &mut Self {
         self.longitude = Some(x);
         self
     }
 
-    pub fn latitude(&mut self, y: f32) -> &mut Self {
+    pub fn latitude(&mut self, y: f64) -> &mut Self {
         self.latitude = Some(y);
         self
     }
 
-    pub fn coord(&mut self, longitude: f32, latitude: f32) -> &mut Self {
+    pub fn coord(&mut self, longitude: f64, latitude: f64) -> &mut Self {
         self.longitude = Some(longitude);
         self.latitude = Some(latitude);
         self
@@ -89,11 +111,25 @@ impl KeywordRequest {
         self
     }
 
-    pub fn rect(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) -> &mut Self {
+    pub fn rect(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) -> &mut Self {
         self.rect = Some((x1, y1, x2, y2));
         self
     }
 
+    /// Like [`rect`](Self::rect), with corners in another system. The rectangle is converted to
+    /// the WGS84 box enclosing it.
+    pub fn rect_projected(
+        &mut self,
+        projection: Projection,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> &mut Self {
+        let (x1, y1, x2, y2) = proj::to_wgs84_rect(projection, x1, y1, x2, y2);
+        self.rect(x1, y1, x2, y2)
+    }
+
     pub fn page(&mut self, page: usize) -> &mut Self {
         self.page = page;
         self
@@ -109,12 +145,30 @@ impl KeywordRequest {
         self
     }
 
-    pub async fn get(&self) -> Result<KeywordResponse, failure::Error> {
-        static API_PATH: &'static str = "/search/keyword.json";
+    /// Requests following pages concurrently while the stream is consumed. At most `pages` pages
+    /// beyond the current one are in flight; `0` (the default) fetches one page at a time.
+    pub fn prefetch(&mut self, pages: usize) -> &mut Self {
+        self.prefetch = pages;
+        self
+    }
+
+    pub async fn get(&self) -> Result<KeywordResponse, Error> {
+        self.get_page(self.page).await
+    }
+
+    /// Streams the places of every page, starting from the page set with [`page`](Self::page).
+    pub fn stream(&self) -> impl Stream<Item = Result<Place, Error>> + '_ {
+        paginate(self.page, self.size, self.prefetch, move |page| {
+            self.get_page(page).map_ok(Into::into)
+        })
+    }
+
+    async fn get_page(&self, page: usize) -> Result<KeywordResponse, Error> {
+        static API_PATH: &str = "/search/keyword.json";
 
         let mut params = vec![
             ("query", self.query.clone()),
-            ("page", self.page.to_string()),
+            ("page", page.to_string()),
             ("size", self.size.to_string()),
             ("sort", self.sort.to_string()),
         ];
@@ -135,7 +189,7 @@ impl KeywordRequest {
             params.push(("rect", format!("{},{},{},{}", x1, y1, x2, y2)));
         }
 
-        let resp = request::<RawResponse>(&self.base_url, API_PATH, &params, &self.app_key).await?;
+        let resp = request::<RawResponse>(&self.client, API_PATH, &params).await?;
         let places = resp.documents.into_iter().map(Into::into).collect();
 
         Ok(KeywordResponse {
@@ -147,12 +201,32 @@ impl KeywordRequest {
     }
 }
 
+impl From<KeywordResponse> for Page<Place> {
+    fn from(resp: KeywordResponse) -> Self {
+        Page {
+            items: resp.places,
+            pageable_count: resp.pageable_count,
+            is_end: resp.is_end,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RawResponse {
     documents: Vec<RawPlace>,
     meta: Meta,
 }
 
+impl Documents for RawResponse {
+    fn document_count(&self) -> Option<usize> {
+        Some(self.documents.len())
+    }
+
+    fn total_count(&self) -> Option<usize> {
+        Some(self.meta.total_count)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RawPlace {
     id: String,
@@ -178,10 +252,86 @@ impl From<RawPlace> for Place {
             phone: raddr.phone,
             address: raddr.address_name,
             road_address: raddr.road_address_name,
-            longitude: raddr.x.parse::<f32>().ok(),
-            latitude: raddr.y.parse::<f32>().ok(),
+            longitude: raddr.x.parse::<f64>().ok(),
+            latitude: raddr.y.parse::<f64>().ok(),
             url: raddr.place_url,
             distance: raddr.distance.parse::<usize>().ok(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{transport::MemoryTransport, CategoryGroup, KakaoClient};
+
+    #[tokio::test]
+    async fn test_parse_place() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            "/search/keyword.json",
+            r#"{
+  "meta": { "same_name": null, "pageable_count": 2, "total_count": 2, "is_end": true },
+  "documents": [
+    {
+      "place_name": "스타벅스 삼성역점",
+      "distance": "120",
+      "place_url": "http://place.map.kakao.com/27290826",
+      "category_name": "음식점 > 카페 > 커피전문점 > 스타벅스",
+      "address_name": "서울 강남구 삼성동 159-9",
+      "road_address_name": "서울 강남구 테헤란로 522",
+      "id": "27290826",
+      "phone": "1522-3232",
+      "category_group_code": "CE7",
+      "category_group_name": "카페",
+      "x": "127.06283102249932",
+      "y": "37.508504004386"
+    },
+    {
+      "place_name": "이름 없는 곳",
+      "distance": "",
+      "place_url": "",
+      "category_name": "",
+      "address_name": "",
+      "road_address_name": "",
+      "id": "",
+      "phone": "",
+      "category_group_code": "",
+      "category_group_name": "",
+      "x": "",
+      "y": ""
+    }
+  ]
+}"#,
+        );
+        let client = KakaoClient::builder("key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let resp = client.keyword("스타벅스").get().await.unwrap();
+        assert_eq!(resp.total_count, 2);
+
+        let place = &resp.places[0];
+        assert_eq!(place.id, Some(27290826));
+        assert_eq!(place.category, "카페");
+        assert!(matches!(place.category_group, Some(CategoryGroup::Cafe)));
+        assert_eq!(place.longitude, Some(127.06283102249932));
+        assert_eq!(place.latitude, Some(37.508504004386));
+        assert_eq!(place.distance, Some(120));
+
+        let place = &resp.places[1];
+        assert_eq!(place.id, None);
+        assert!(place.category_group.is_none());
+        assert_eq!(place.longitude, None);
+        assert_eq!(place.distance, None);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("authorization"), Some("KakaoAK key"));
+        assert_eq!(
+            requests[0].url.as_str(),
+            "https://dapi.kakao.com/v2/local/search/keyword.json\
+             ?query=%EC%8A%A4%ED%83%80%EB%B2%85%EC%8A%A4&page=1&size=15&sort=accuracy"
+        );
+    }
+}
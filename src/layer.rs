@@ -0,0 +1,283 @@
+//! Hooks around every request sent by a client.
+//!
+//! A [`Layer`] sees each outgoing request before it is handed to the
+//! [`Transport`](crate::transport::Transport), and its response or error afterwards. Layers are
+//! added with [`KakaoClientBuilder::layer`](crate::KakaoClientBuilder::layer) and run in the
+//! order they were added on the way out, and in reverse order on the way back. They run once per
+//! attempt, after the rate limiter, and not at all for responses served from the cache.
+//!
+//! ```no_run
+//! # async fn foo() {
+//! use daummap::layer::{Layer, LoggingLayer, MetricsLayer, OutgoingRequest};
+//!
+//! #[derive(Debug)]
+//! struct CorrelationId;
+//!
+//! impl Layer for CorrelationId {
+//!     fn on_request(&self, req: &mut OutgoingRequest) {
+//!         req.headers.push(("X-Request-Id".to_string(), "1234".to_string()));
+//!     }
+//! }
+//!
+//! let metrics = MetricsLayer::new();
+//! let client = daummap::KakaoClient::builder("API_KEY")
+//!     .layer(CorrelationId)
+//!     .layer(LoggingLayer::new())
+//!     .layer(metrics.clone())
+//!     .build()
+//!     .unwrap();
+//! let resp = client.keyword("카카오프렌즈").get().await.unwrap();
+//! println!("{:?}", metrics.get("search/keyword.json"));
+//! # }
+//! ```
+
+use {
+    crate::{transport::HttpResponse, Error},
+    reqwest::Url,
+    std::{
+        collections::BTreeMap,
+        fmt,
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+};
+
+static REDACTED: &str = "***";
+
+/// Interceptor called around every request sent by a client.
+pub trait Layer: fmt::Debug + Send + Sync {
+    /// Called before the request is sent. Changes made to `req` are sent.
+    fn on_request(&self, req: &mut OutgoingRequest) {
+        let _ = req;
+    }
+
+    /// Called with the response, successful or not, or the transport error, and the time it
+    /// took.
+    fn on_response(
+        &self,
+        req: &OutgoingRequest,
+        resp: Result<&HttpResponse, &Error>,
+        elapsed: Duration,
+    ) {
+        let _ = (req, resp, elapsed);
+    }
+}
+
+/// A request about to be sent.
+///
+/// The URL is built from `base_url`, `path` and `params` after every layer has run, so a layer
+/// can point requests at a proxy by replacing `base_url`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingRequest {
+    pub base_url: String,
+    /// Path under `base_url`, without the leading slash, e.g. `search/keyword.json`.
+    pub path: String,
+    pub params: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Writes a line per response with the method, URL, headers, status and time taken. The app key
+/// is redacted.
+///
+/// Lines go to stderr unless another writer is set with [`with_writer`](Self::with_writer).
+#[derive(Clone)]
+pub struct LoggingLayer {
+    writer: Arc<dyn Fn(&str) + Send + Sync>,
+}
+
+/// Counts requests, errors and time spent per endpoint.
+///
+/// Clones share the same counters, so keep one to read them after handing another to the client.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsLayer {
+    endpoints: Arc<Mutex<BTreeMap<String, EndpointStats>>>,
+}
+
+/// Counters of a [`MetricsLayer`] for one endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EndpointStats {
+    /// Attempts sent, including retries.
+    pub requests: u64,
+    /// Attempts that failed in the transport or got a non-2xx status.
+    pub errors: u64,
+    pub total_time: Duration,
+}
+
+/// Hides the app key from the layer it wraps.
+///
+/// The wrapped layer gets a [`redacted`](OutgoingRequest::redacted) copy of each request, so it
+/// can log headers and URLs safely. Changes it makes to that copy are not sent.
+#[derive(Debug, Clone)]
+pub struct RedactKey<L> {
+    inner: L,
+}
+
+impl OutgoingRequest {
+    /// The URL the request is sent to.
+    pub fn url(&self) -> Result<Url, Error> {
+        let base_url = if self.base_url.ends_with('/') {
+            self.base_url.to_string()
+        } else {
+            self.base_url.to_string() + "/"
+        };
+        let url = Url::parse(&base_url)
+            .and_then(|base| base.join(self.path.trim_start_matches('/')))
+            .and_then(|url| Url::parse_with_params(url.as_str(), &self.params))?;
+        Ok(url)
+    }
+
+    /// Value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The app key sent in the `Authorization` header.
+    pub fn app_key(&self) -> Option<&str> {
+        self.header("Authorization")
+            .and_then(|value| value.strip_prefix("KakaoAK "))
+            .filter(|key| !key.is_empty())
+    }
+
+    /// A copy with the app key replaced by `***` in the `Authorization` header and in the
+    /// parameters whose value is the app key. The rest of the request is left as it is, so a
+    /// query that happens to contain the key is logged unchanged.
+    pub fn redacted(&self) -> OutgoingRequest {
+        let key = match self.app_key() {
+            Some(key) => key.to_string(),
+            None => return self.clone(),
+        };
+        let mut req = self.clone();
+        for (name, value) in &mut req.headers {
+            if name.eq_ignore_ascii_case("Authorization") {
+                *value = format!("KakaoAK {}", REDACTED);
+            }
+        }
+        for (_, value) in &mut req.params {
+            if *value == key {
+                *value = REDACTED.to_string();
+            }
+        }
+        req
+    }
+}
+
+impl LoggingLayer {
+    pub fn new() -> Self {
+        Self::with_writer(|line| eprintln!("{}", line))
+    }
+
+    pub fn with_writer(writer: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        LoggingLayer {
+            writer: Arc::new(writer),
+        }
+    }
+}
+
+impl Default for LoggingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for LoggingLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggingLayer").finish_non_exhaustive()
+    }
+}
+
+impl Layer for LoggingLayer {
+    fn on_response(
+        &self,
+        req: &OutgoingRequest,
+        resp: Result<&HttpResponse, &Error>,
+        elapsed: Duration,
+    ) {
+        let req = req.redacted();
+        let url = req
+            .url()
+            .map(String::from)
+            .unwrap_or_else(|_| format!("{}/{}", req.base_url, req.path));
+        let headers = req
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let outcome = match resp {
+            Ok(resp) => resp.status.to_string(),
+            Err(e) => format!("error: {}", e),
+        };
+        (self.writer)(&format!(
+            "GET {} [{}] -> {} ({} ms)",
+            url,
+            headers,
+            outcome,
+            elapsed.as_millis()
+        ));
+    }
+}
+
+impl MetricsLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counters of the endpoint at `path`, e.g. `search/keyword.json`.
+    pub fn get(&self, path: &str) -> EndpointStats {
+        let endpoints = self.endpoints.lock().unwrap();
+        endpoints
+            .get(path.trim_start_matches('/'))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Counters of every endpoint requested so far, by path.
+    pub fn snapshot(&self) -> BTreeMap<String, EndpointStats> {
+        self.endpoints.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        self.endpoints.lock().unwrap().clear();
+    }
+}
+
+impl Layer for MetricsLayer {
+    fn on_response(
+        &self,
+        req: &OutgoingRequest,
+        resp: Result<&HttpResponse, &Error>,
+        elapsed: Duration,
+    ) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let stats = endpoints.entry(req.path.clone()).or_default();
+        stats.requests += 1;
+        if !resp.is_ok_and(|resp| resp.status.is_success()) {
+            stats.errors += 1;
+        }
+        stats.total_time += elapsed;
+    }
+}
+
+impl<L: Layer> RedactKey<L> {
+    pub fn new(inner: L) -> Self {
+        RedactKey { inner }
+    }
+}
+
+impl<L: Layer> Layer for RedactKey<L> {
+    fn on_request(&self, req: &mut OutgoingRequest) {
+        self.inner.on_request(&mut req.redacted());
+    }
+
+    fn on_response(
+        &self,
+        req: &OutgoingRequest,
+        resp: Result<&HttpResponse, &Error>,
+        elapsed: Duration,
+    ) {
+        self.inner.on_response(&req.redacted(), resp, elapsed);
+    }
+}
@@ -4,6 +4,27 @@
 //!
 //! # Examples
 //!
+//! ## Shared Client
+//!
+//! Create a [`KakaoClient`] once and build every request from it, so connections are pooled and
+//! reused between calls.
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! let client = daummap::KakaoClient::new(APP_KEY);
+//! let resp = client.keyword("카카오프렌즈").size(5).get().await.unwrap();
+//! for p in resp.places {
+//!     println!("{}", p.name);
+//! }
+//! let resp = client.address("전북 삼성동 100").get().await.unwrap();
+//! for addr in resp.addresses {
+//!     println!("{:?}", addr.address);
+//! }
+//! # }
+//! ```
+//!
 //! ## Address Search
 //!
 //! ```no_run
@@ -52,6 +73,24 @@
 //! # }
 //! ```
 //!
+//! ## Coordinate System Transformation
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! let resp = daummap::TransCoordRequest::new(APP_KEY, 160710.37729270622, -4388.879299157299)
+//!     .input_coord(daummap::CoordSystem::Wtm)
+//!     .output_coord(daummap::CoordSystem::Wgs84)
+//!     .get()
+//!     .await
+//!     .unwrap();
+//! for coord in resp {
+//!     println!("{}, {}", coord.x, coord.y);
+//! }
+//! # }
+//! ```
+//!
 //! ## Keyword Search
 //!
 //! ```no_run
@@ -92,27 +131,124 @@
 //! }
 //! # }
 //! ```
+//!
+//! ## Pagination
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! use futures::TryStreamExt;
+//!
+//! let client = daummap::KakaoClient::new(APP_KEY);
+//! let places = client
+//!     .keyword("카카오프렌즈")
+//!     .prefetch(2)
+//!     .stream()
+//!     .try_collect::<Vec<_>>()
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! ## Response Cache
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! use std::time::Duration;
+//!
+//! let client = daummap::KakaoClient::builder(APP_KEY)
+//!     .cache(daummap::ResponseCache::new(1000).ttl(Duration::from_secs(600)).clone())
+//!     .build()
+//!     .unwrap();
+//! let resp = client.keyword("강남역").get().await.unwrap();
+//! // Answered from the cache.
+//! let resp = client.keyword("강남역").get().await.unwrap();
+//! // Sent to Kakao.
+//! let resp = client.keyword("강남역").no_cache().get().await.unwrap();
+//! println!("{:?}", client.cache().unwrap().stats());
+//! # }
+//! ```
+//!
+//! # Features
+//!
+//! - `batch`: bulk geocoding of CSV files with resumable progress. See the `batch` module.
+//! - `blocking`: a synchronous client and request builders, for programs without an async
+//!   runtime. See the `blocking` module.
+//! - `cli`: the `daummap` command-line client. Run `daummap --help` for the subcommands.
+//! - `geo`: conversions of the results to `geo-types` points and GeoJSON features, and request
+//!   builders taking points and rectangles.
+//! - `serde`: `Serialize` and `Deserialize` for the result types. Category groups and coordinate
+//!   systems are serialized as their Kakao codes.
+//! - `testing`: `MockKakao`, a mock server of the API with bundled fixtures and a record mode for
+//!   capturing new ones. See the `testing` module.
+//! - `tracing`: a `daummap.request` span per API call with the endpoint, paging, result counts,
+//!   status, latency, attempts and cache outcome. Query text is recorded only when enabled with
+//!   `KakaoClientBuilder::trace_queries`.
 
 pub mod address;
+#[cfg(feature = "batch")]
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
 pub mod category;
+pub mod client;
 pub mod coord;
+pub mod error;
+#[cfg(feature = "geo")]
+pub mod geo;
 pub mod keyword;
+pub mod layer;
+mod paginate;
+pub mod proj;
+pub mod ratelimit;
+pub mod retry;
+pub mod sweep;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod transcoord;
+pub mod transport;
 
 pub use crate::{
-    address::{Address, AddressRequest, AddressResponse, LandLotAddress, RoadAddress},
-    category::{CategoryGroup, CategoryRequest, CategoryResponse},
-    coord::{CoordRequest, Region},
+    address::{
+        Address, AddressRequest, AddressResponse, AddressType, AnalyzeType, LandLotAddress,
+        RoadAddress,
+    },
+    cache::{CacheStats, ResponseCache},
+    category::{CategoryGroup, CategoryRequest, CategoryResponse, ParseCategoryGroup},
+    client::{KakaoClient, KakaoClientBuilder},
+    coord::{CoordRequest, Region, RegionType},
+    error::{Error, KakaoApiError},
     keyword::{KeywordRequest, KeywordResponse, Place},
+    ratelimit::{DailyUsage, RateLimiter},
+    retry::RetryPolicy,
+    transcoord::{CoordSystem, ParseCoordSystem, TransCoord, TransCoordRequest},
 };
 
+/// Records a field on the span of the current API call when the `tracing` feature is enabled.
+macro_rules! record {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, $value);
+    };
+}
+
 use {
-    reqwest::{Client, Url},
+    crate::{cache::Lookup, layer::OutgoingRequest, transport::HttpRequest},
     serde::{de::DeserializeOwned, Deserialize},
+    std::{fmt, time::Instant},
 };
 
-pub(crate) static KAKAO_LOCAL_API_BASE_URL: &'static str = "https://dapi.kakao.com/v2/local";
+pub(crate) static KAKAO_LOCAL_API_BASE_URL: &str = "https://dapi.kakao.com/v2/local";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Sort {
     Distance,
     Accuracy,
@@ -125,58 +261,174 @@ pub(crate) struct Meta {
     is_end: bool,
 }
 
-impl ToString for Sort {
-    fn to_string(&self) -> String {
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Sort::Distance => "distance".to_string(),
-            Sort::Accuracy => "accuracy".to_string(),
+            Sort::Distance => f.write_str("distance"),
+            Sort::Accuracy => f.write_str("accuracy"),
         }
     }
 }
 
-pub(crate) async fn request<T: DeserializeOwned>(
-    base_url: &str,
+/// A raw response whose size is recorded on the span of the API call.
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+pub(crate) trait Documents {
+    fn document_count(&self) -> Option<usize> {
+        None
+    }
+
+    fn total_count(&self) -> Option<usize> {
+        None
+    }
+}
+
+pub(crate) async fn request<T: DeserializeOwned + Documents>(
+    client: &KakaoClient,
     path: &str,
     params: &[(&str, String)],
-    key: &str,
-) -> Result<T, failure::Error> {
-    let base_url = if base_url.ends_with('/') {
-        base_url.to_string()
-    } else {
-        base_url.to_string() + "/"
+) -> Result<T, Error> {
+    let path = path.trim_start_matches('/');
+    let fut = fetch(client, path, params);
+    #[cfg(feature = "tracing")]
+    let fut = trace::instrument(client, path, params, fut);
+    fut.await
+}
+
+async fn fetch<T: DeserializeOwned + Documents>(
+    client: &KakaoClient,
+    path: &str,
+    params: &[(&str, String)],
+) -> Result<T, Error> {
+    let cached = match client.cache {
+        Some(ref cache) => cache.lookup(&client.base_url, path, params),
+        None => {
+            record!("cache", "disabled");
+            Lookup::Missing
+        }
+    };
+    let stale = match cached {
+        Lookup::Fresh(body) => {
+            record!("cache", "hit");
+            return parse(&body);
+        }
+        Lookup::Stale(body) => {
+            record!("cache", "stale");
+            Some(body)
+        }
+        Lookup::Missing if client.cache.as_ref().is_some_and(ResponseCache::is_offline) => {
+            record!("cache", "miss");
+            return Err(Error::CacheMiss {
+                path: path.to_string(),
+            });
+        }
+        Lookup::Missing => {
+            if client.cache.is_some() {
+                record!("cache", "miss");
+            }
+            None
+        }
+    };
+
+    let body = match (send_with_retry(client, path, params).await, stale) {
+        (Ok(body), _) => body,
+        (Err(e), Some(stale)) if RetryPolicy::is_transient(&e) => {
+            record!("cache", "stale_fallback");
+            record!("error", trace::describe(&e).as_str());
+            return parse(&stale);
+        }
+        (Err(e), _) => return Err(e),
+    };
+    let resp = parse(&body)?;
+    if let Some(ref cache) = client.cache {
+        cache.insert(&client.base_url, path, params, &body);
+    }
+    Ok(resp)
+}
+
+fn parse<T: DeserializeOwned + Documents>(body: &str) -> Result<T, Error> {
+    let resp = serde_json::from_str::<T>(body).map_err(|e| Error::deserialize(e, body))?;
+    #[cfg(feature = "tracing")]
+    trace::record_documents(&resp);
+    Ok(resp)
+}
+
+async fn send_with_retry(
+    client: &KakaoClient,
+    path: &str,
+    params: &[(&str, String)],
+) -> Result<String, Error> {
+    let mut attempt = 1;
+    loop {
+        record!("attempts", attempt);
+        match send(client, path, params).await {
+            Ok(body) => return Ok(body),
+            Err(e) if client.retry.is_retryable(&e) => {
+                if attempt >= client.retry.attempts() {
+                    if attempt == 1 {
+                        return Err(e);
+                    }
+                    return Err(Error::RetriesExhausted {
+                        attempts: attempt,
+                        source: Box::new(e),
+                    });
+                }
+                tokio::time::sleep(client.retry.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn send(
+    client: &KakaoClient,
+    path: &str,
+    params: &[(&str, String)],
+) -> Result<String, Error> {
+    if let Some(ref limiter) = client.rate_limiter {
+        limiter.acquire().await?;
+    }
+
+    let mut req = OutgoingRequest {
+        base_url: client.base_url.clone(),
+        path: path.to_string(),
+        params: params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect(),
+        headers: vec![(
+            "Authorization".to_string(),
+            format!("KakaoAK {}", client.app_key),
+        )],
     };
-    let path = if path.starts_with('/') {
-        path.trim_start_matches('/').to_string()
-    } else {
-        path.to_string()
+    for layer in &client.layers {
+        layer.on_request(&mut req);
+    }
+
+    let http_req = HttpRequest {
+        url: req.url()?,
+        headers: req.headers.clone(),
     };
+    let started = Instant::now();
+    let resp = client.transport.send(http_req).await;
+    let elapsed = started.elapsed();
+    for layer in client.layers.iter().rev() {
+        layer.on_response(&req, resp.as_ref(), elapsed);
+    }
 
-    let key = key.to_string();
-
-    let url = Url::parse(&base_url)
-        .and_then(|base| base.join(&path))
-        .and_then(|url| Url::parse_with_params(url.as_str(), params))?;
-    let resp = Client::new()
-        .get(url)
-        .header("Authorization", format!("KakaoAK {}", key))
-        .body("")
-        .send()
-        .await?;
-    let resp = resp.error_for_status()?;
-    Ok(resp.json().await?)
+    let resp = resp?;
+    record!("status", resp.status.as_u16());
+    if !resp.status.is_success() {
+        return Err(Error::from_status(resp.status, &resp.body));
+    }
+    Ok(resp.body)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::convert::Infallible;
-
     use {
-        crate::request,
-        hyper::{
-            header::HeaderValue,
-            service::{make_service_fn, service_fn},
-            Body, Response, Server,
-        },
+        crate::{request, testing::MockKakao, Documents, Error},
+        reqwest::StatusCode,
         serde::Deserialize,
     };
 
@@ -185,59 +437,84 @@ mod tests {
         bar: String,
     }
 
+    impl Documents for Foo {}
+
     #[tokio::test]
     async fn test_request() {
-        let (called_sender, mut called_receiver) = tokio::sync::mpsc::channel(2);
-        let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-
-        let service = make_service_fn(move |_| {
-            let called_sender = called_sender.clone();
-            async move {
-                Ok::<_, Infallible>(service_fn(move |req| {
-                    let called_sender = called_sender.clone();
-                    async move {
-                        let uri = req.uri();
-                        assert_eq!(uri.path(), "/api/foo/bar");
-                        assert_eq!(uri.query(), Some("baz=bax"));
-
-                        let headers = req.headers();
-                        assert_eq!(
-                            headers.get("Authorization"),
-                            Some(&HeaderValue::from_static("KakaoAK key"))
-                        );
-
-                        called_sender.send(()).await.unwrap();
-
-                        Ok::<_, Infallible>(Response::<Body>::new(r#"{ "bar": "foobar" }"#.into()))
-                    }
-                }))
-            }
-        });
+        let mock = MockKakao::start().await;
+        mock.on("/api/foo/bar").respond(r#"{ "bar": "foobar" }"#);
 
-        let server = Server::bind(&"0.0.0.0:12121".parse().unwrap())
-            .serve(service)
-            .with_graceful_shutdown(async {
-                shutdown_receiver.await.unwrap();
-            });
+        let client = mock
+            .builder("key")
+            .base_url(&format!("{}/api", mock.url()))
+            .build()
+            .unwrap();
+        let resp = request::<Foo>(&client, "/foo/bar", &[("baz", "bax".to_string())])
+            .await
+            .unwrap();
 
-        tokio::spawn(async {
-            if let Err(e) = server.await {
-                panic!("{}", e)
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/api/foo/bar");
+        assert_eq!(requests[0].query, "baz=bax");
+        assert_eq!(requests[0].app_key.as_deref(), Some("key"));
+
+        assert_eq!(&resp.bar, "foobar");
+    }
+
+    #[tokio::test]
+    async fn test_request_error() {
+        let mock = MockKakao::start().await;
+        mock.on("/api")
+            .status(StatusCode::UNAUTHORIZED)
+            .respond(r#"{"errorType":"AccessDeniedError","message":"cannot find appKey"}"#);
+        mock.on("/quota")
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .respond(r#"{"msg":"API limit has been exceeded.","code":-10}"#);
+        mock.on("/status")
+            .status(StatusCode::BAD_GATEWAY)
+            .respond("<html>Bad Gateway</html>");
+        mock.on("/foo").respond(r#"{ "baz": "foobar" }"#);
+
+        let client = mock.client("key");
+        match request::<Foo>(&client, "/api", &[]).await {
+            Err(Error::Api(e)) => {
+                assert_eq!(e.status, StatusCode::UNAUTHORIZED);
+                assert_eq!(e.error_type.as_deref(), Some("AccessDeniedError"));
+                assert_eq!(e.code, None);
+                assert_eq!(e.message, "cannot find appKey");
+                assert!(e.is_invalid_key());
+                assert!(!e.is_quota_exceeded());
             }
-        });
+            other => panic!("unexpected result: {:?}", other.map(|foo| foo.bar)),
+        }
 
-        let resp = request::<Foo>(
-            "http://localhost:12121/api",
-            "/foo/bar",
-            &[("baz", "bax".to_string())],
-            "key",
-        )
-        .await
-        .unwrap();
+        match request::<Foo>(&client, "/quota", &[]).await {
+            Err(Error::Api(e)) => {
+                assert_eq!(e.status, StatusCode::TOO_MANY_REQUESTS);
+                assert_eq!(e.error_type, None);
+                assert_eq!(e.code, Some(-10));
+                assert_eq!(e.message, "API limit has been exceeded.");
+                assert!(e.is_quota_exceeded());
+                assert!(!e.is_invalid_key());
+                assert!(!e.is_invalid_argument());
+            }
+            other => panic!("unexpected result: {:?}", other.map(|foo| foo.bar)),
+        }
 
-        shutdown_sender.send(()).unwrap();
-        called_receiver.try_recv().unwrap();
+        match request::<Foo>(&client, "/status", &[]).await {
+            Err(Error::Status { status, body }) => {
+                assert_eq!(status, StatusCode::BAD_GATEWAY);
+                assert_eq!(body, "<html>Bad Gateway</html>");
+            }
+            other => panic!("unexpected result: {:?}", other.map(|foo| foo.bar)),
+        }
 
-        assert_eq!(&resp.bar, "foobar");
+        match request::<Foo>(&client, "/foo", &[]).await {
+            Err(Error::Deserialize { body, .. }) => {
+                assert_eq!(body, r#"{ "baz": "foobar" }"#);
+            }
+            other => panic!("unexpected result: {:?}", other.map(|foo| foo.bar)),
+        }
     }
 }
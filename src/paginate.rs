@@ -0,0 +1,97 @@
+use {
+    crate::Error,
+    futures::{
+        stream::{self, FuturesOrdered, Stream, StreamExt},
+        Future,
+    },
+    std::vec,
+};
+
+/// Kakao never serves more than this many pages for a query.
+pub(crate) const MAX_PAGE: usize = 45;
+
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    pub pageable_count: usize,
+    pub is_end: bool,
+}
+
+struct State<T, F, Fut: Future> {
+    fetch: F,
+    in_flight: FuturesOrdered<Fut>,
+    items: vec::IntoIter<T>,
+    next_page: usize,
+    last_page: usize,
+    size: usize,
+    prefetch: usize,
+    done: bool,
+}
+
+/// Flattens the pages starting from `first_page` into a stream of items.
+///
+/// Stops after the page reporting `is_end`, the last page covered by `pageable_count` or
+/// [`MAX_PAGE`], whichever comes first. Up to `prefetch` pages beyond the current one are
+/// requested concurrently; prefetched pages past the end are discarded, also when a later page
+/// reports a smaller `pageable_count` than an earlier one.
+pub(crate) fn paginate<'a, T, F, Fut>(
+    first_page: usize,
+    size: usize,
+    prefetch: usize,
+    fetch: F,
+) -> impl Stream<Item = Result<T, Error>> + 'a
+where
+    T: 'a,
+    F: Fn(usize) -> Fut + 'a,
+    Fut: Future<Output = Result<Page<T>, Error>> + 'a,
+{
+    let state = State {
+        fetch,
+        in_flight: FuturesOrdered::new(),
+        items: Vec::new().into_iter(),
+        next_page: first_page.max(1),
+        last_page: MAX_PAGE,
+        size: size.max(1),
+        prefetch,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.items.next() {
+                return Some((Ok(item), state));
+            }
+            if state.done {
+                return None;
+            }
+
+            while state.in_flight.len() <= state.prefetch && state.next_page <= state.last_page {
+                state.in_flight.push_back((state.fetch)(state.next_page));
+                state.next_page += 1;
+            }
+
+            match state.in_flight.next().await {
+                None => return None,
+                Some(Err(e)) => {
+                    state.done = true;
+                    return Some((Err(e), state));
+                }
+                Some(Ok(page)) => {
+                    // Pages come back in order, right before the ones still in flight.
+                    let number = state.next_page - state.in_flight.len() - 1;
+                    if number > state.last_page {
+                        state.done = true;
+                        state.in_flight = FuturesOrdered::new();
+                        continue;
+                    }
+                    let pageable_pages = page.pageable_count.div_ceil(state.size);
+                    state.last_page = state.last_page.min(pageable_pages);
+                    if page.is_end || number >= state.last_page {
+                        state.done = true;
+                        state.in_flight = FuturesOrdered::new();
+                    }
+                    state.items = page.items.into_iter();
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,569 @@
+//! Offline coordinate projection.
+//!
+//! Converts coordinates between WGS84 and the projected coordinate systems used in Korea without
+//! calling the transcoord API. Bessel based systems (the Korean 1985 datum behind Kakao's `TM` and
+//! `CONGNAMUL`) are shifted to GRS80 with a seven parameter Helmert transformation. GRS80 and
+//! WGS84 are treated as identical, which is accurate to well below a millimeter.
+//!
+//! ```
+//! use daummap::proj::Projection;
+//!
+//! let (x, y) = Projection::Korea2000Central.from_wgs84(127.0, 38.0);
+//! assert!((x - 200000.0).abs() < 1e-6 && (y - 600000.0).abs() < 1e-6);
+//!
+//! let (lon, lat) = Projection::Korea2000Central.to_wgs84(x, y);
+//! assert!((lon - 127.0).abs() < 1e-9 && (lat - 38.0).abs() < 1e-9);
+//! ```
+
+use {
+    crate::CoordSystem,
+    std::{convert::TryFrom, f64::consts::PI},
+};
+
+/// Coordinate systems supported by [`transform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Projection {
+    /// Longitude and latitude in degrees.
+    Wgs84,
+    /// Kakao's `WTM`, the GRS80 central belt with a false northing of 500000 (EPSG:5181).
+    Wtm,
+    /// Kakao's `WCONGNAMUL`, `WTM` scaled by 2.5.
+    WCongnamul,
+    /// Kakao's `TM`, the Korean 1985 (Bessel) modified central belt (EPSG:5174).
+    Tm,
+    /// Kakao's `CONGNAMUL`, `TM` scaled by 2.5.
+    Congnamul,
+    /// Korea 2000 west belt (EPSG:5185).
+    Korea2000West,
+    /// Korea 2000 central belt (EPSG:5186).
+    Korea2000Central,
+    /// Korea 2000 east belt (EPSG:5187).
+    Korea2000East,
+    /// Korea 2000 east sea belt (EPSG:5188).
+    Korea2000EastSea,
+    /// Korea 2000 unified coordinate system (EPSG:5179).
+    UtmK,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Ellipsoid {
+    a: f64,
+    f: f64,
+}
+
+const GRS80: Ellipsoid = Ellipsoid {
+    a: 6378137.0,
+    f: 1.0 / 298.257222101,
+};
+
+const BESSEL: Ellipsoid = Ellipsoid {
+    a: 6377397.155,
+    f: 1.0 / 299.1528128,
+};
+
+/// Korean 1985 to WGS84 in the position vector convention, as in the EPSG:5174 definition:
+/// translations in meters, rotations in arc seconds and scale in ppm.
+const KOREAN_1985_TO_WGS84: [f64; 7] = [-145.907, 505.034, 685.756, -1.162, 2.347, 1.592, 6.342];
+
+#[derive(Debug, Clone, Copy)]
+struct TransverseMercator {
+    ellipsoid: Ellipsoid,
+    lon0: f64,
+    lat0: f64,
+    k0: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl Projection {
+    fn transverse_mercator(self) -> Option<TransverseMercator> {
+        use self::Projection::*;
+
+        let (ellipsoid, lon0, k0, false_easting, false_northing) = match self {
+            Wgs84 => return None,
+            Wtm => (GRS80, 127.0, 1.0, 200000.0, 500000.0),
+            WCongnamul => (GRS80, 127.0, 2.5, 500000.0, 1250000.0),
+            Tm => (BESSEL, 127.0 + 10.405 / 3600.0, 1.0, 200000.0, 500000.0),
+            Congnamul => (BESSEL, 127.0 + 10.405 / 3600.0, 2.5, 500000.0, 1250000.0),
+            Korea2000West => (GRS80, 125.0, 1.0, 200000.0, 600000.0),
+            Korea2000Central => (GRS80, 127.0, 1.0, 200000.0, 600000.0),
+            Korea2000East => (GRS80, 129.0, 1.0, 200000.0, 600000.0),
+            Korea2000EastSea => (GRS80, 131.0, 1.0, 200000.0, 600000.0),
+            UtmK => (GRS80, 127.5, 0.9996, 1000000.0, 2000000.0),
+        };
+
+        Some(TransverseMercator {
+            ellipsoid,
+            lon0,
+            lat0: 38.0,
+            k0,
+            false_easting,
+            false_northing,
+        })
+    }
+
+    /// Converts a coordinate of this system to WGS84 longitude and latitude.
+    pub fn to_wgs84(self, x: f64, y: f64) -> (f64, f64) {
+        match self.transverse_mercator() {
+            None => (x, y),
+            Some(tm) => {
+                let (lon, lat) = tm.inverse(x, y);
+                if tm.is_bessel() {
+                    shift_datum(lon, lat, &BESSEL, &GRS80, false)
+                } else {
+                    (lon, lat)
+                }
+            }
+        }
+    }
+
+    /// Converts WGS84 longitude and latitude to a coordinate of this system.
+    pub fn from_wgs84(self, longitude: f64, latitude: f64) -> (f64, f64) {
+        match self.transverse_mercator() {
+            None => (longitude, latitude),
+            Some(tm) => {
+                let (lon, lat) = if tm.is_bessel() {
+                    grs80_to_bessel(longitude, latitude)
+                } else {
+                    (longitude, latitude)
+                };
+                tm.forward(lon, lat)
+            }
+        }
+    }
+}
+
+/// Kakao coordinate systems without an offline equivalent are rejected with the system itself.
+impl TryFrom<CoordSystem> for Projection {
+    type Error = CoordSystem;
+
+    fn try_from(coord_system: CoordSystem) -> Result<Self, CoordSystem> {
+        Ok(match coord_system {
+            CoordSystem::Wgs84 => Projection::Wgs84,
+            CoordSystem::Wtm => Projection::Wtm,
+            CoordSystem::WCongnamul => Projection::WCongnamul,
+            CoordSystem::Tm => Projection::Tm,
+            CoordSystem::Congnamul => Projection::Congnamul,
+            _ => return Err(coord_system),
+        })
+    }
+}
+
+/// Converts a coordinate from one system to another.
+pub fn transform(from: Projection, to: Projection, x: f64, y: f64) -> (f64, f64) {
+    if from == to {
+        return (x, y);
+    }
+    let (lon, lat) = from.to_wgs84(x, y);
+    to.from_wgs84(lon, lat)
+}
+
+/// Bounding box in WGS84 of a rectangle given in another system.
+pub(crate) fn to_wgs84_rect(
+    projection: Projection,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+) -> (f64, f64, f64, f64) {
+    let corners = [
+        projection.to_wgs84(x1, y1),
+        projection.to_wgs84(x1, y2),
+        projection.to_wgs84(x2, y1),
+        projection.to_wgs84(x2, y2),
+    ];
+    corners.iter().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    )
+}
+
+impl Ellipsoid {
+    fn e2(&self) -> f64 {
+        self.f * (2.0 - self.f)
+    }
+}
+
+impl TransverseMercator {
+    fn is_bessel(&self) -> bool {
+        self.ellipsoid.a == BESSEL.a
+    }
+
+    /// Coefficients of the Krüger series and the rectifying radius `A`.
+    fn series(&self) -> ([f64; 6], [f64; 6], f64) {
+        let f = self.ellipsoid.f;
+        let n = f / (2.0 - f);
+        let (n2, n3, n4, n5, n6) = (n * n, n.powi(3), n.powi(4), n.powi(5), n.powi(6));
+
+        let alpha = [
+            n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0 + 41.0 * n4 / 180.0 - 127.0 * n5 / 288.0
+                + 7891.0 * n6 / 37800.0,
+            13.0 * n2 / 48.0 - 3.0 * n3 / 5.0 + 557.0 * n4 / 1440.0 + 281.0 * n5 / 630.0
+                - 1983433.0 * n6 / 1935360.0,
+            61.0 * n3 / 240.0 - 103.0 * n4 / 140.0
+                + 15061.0 * n5 / 26880.0
+                + 167603.0 * n6 / 181440.0,
+            49561.0 * n4 / 161280.0 - 179.0 * n5 / 168.0 + 6601661.0 * n6 / 7257600.0,
+            34729.0 * n5 / 80640.0 - 3418889.0 * n6 / 1995840.0,
+            212378941.0 * n6 / 319334400.0,
+        ];
+        let beta = [
+            n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0 - n4 / 360.0 - 81.0 * n5 / 512.0
+                + 96199.0 * n6 / 604800.0,
+            n2 / 48.0 + n3 / 15.0 - 437.0 * n4 / 1440.0 + 46.0 * n5 / 105.0
+                - 1118711.0 * n6 / 3870720.0,
+            17.0 * n3 / 480.0 - 37.0 * n4 / 840.0 - 209.0 * n5 / 4480.0 + 5569.0 * n6 / 90720.0,
+            4397.0 * n4 / 161280.0 - 11.0 * n5 / 504.0 - 830251.0 * n6 / 7257600.0,
+            4583.0 * n5 / 161280.0 - 108847.0 * n6 / 3991680.0,
+            20648693.0 * n6 / 638668800.0,
+        ];
+        let radius = self.ellipsoid.a / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0 + n6 / 256.0);
+
+        (alpha, beta, radius)
+    }
+
+    /// Transverse Mercator coordinates on the unit sphere, before scaling by `A`.
+    fn project(&self, alpha: &[f64; 6], lambda: f64, phi: f64) -> (f64, f64) {
+        let e = self.ellipsoid.e2().sqrt();
+        let t = (phi.sin().atanh() - e * (e * phi.sin()).atanh()).sinh();
+        let xi_prime = t.atan2(lambda.cos());
+        let eta_prime = (lambda.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        alpha
+            .iter()
+            .enumerate()
+            .fold((xi_prime, eta_prime), |(xi, eta), (j, alpha)| {
+                let k = 2.0 * (j + 1) as f64;
+                (
+                    xi + alpha * (k * xi_prime).sin() * (k * eta_prime).cosh(),
+                    eta + alpha * (k * xi_prime).cos() * (k * eta_prime).sinh(),
+                )
+            })
+    }
+
+    fn forward(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (alpha, _, radius) = self.series();
+        let (xi0, _) = self.project(&alpha, 0.0, self.lat0.to_radians());
+        let (xi, eta) = self.project(&alpha, (lon - self.lon0).to_radians(), lat.to_radians());
+
+        (
+            self.false_easting + self.k0 * radius * eta,
+            self.false_northing + self.k0 * radius * (xi - xi0),
+        )
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        let (alpha, beta, radius) = self.series();
+        let (xi0, _) = self.project(&alpha, 0.0, self.lat0.to_radians());
+        let xi = (y - self.false_northing) / (self.k0 * radius) + xi0;
+        let eta = (x - self.false_easting) / (self.k0 * radius);
+
+        let (xi_prime, eta_prime) =
+            beta.iter()
+                .enumerate()
+                .fold((xi, eta), |(xi_prime, eta_prime), (j, beta)| {
+                    let k = 2.0 * (j + 1) as f64;
+                    (
+                        xi_prime - beta * (k * xi).sin() * (k * eta).cosh(),
+                        eta_prime - beta * (k * xi).cos() * (k * eta).sinh(),
+                    )
+                });
+
+        let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+        let lambda = eta_prime.sinh().atan2(xi_prime.cos());
+
+        // Geodetic latitude from the conformal one, converging by a factor of e² per step.
+        let e = self.ellipsoid.e2().sqrt();
+        let mut phi = chi;
+        for _ in 0..15 {
+            let next = (chi.sin().atanh() + e * (e * phi.sin()).atanh())
+                .tanh()
+                .asin();
+            if (next - phi).abs() < 1e-15 {
+                phi = next;
+                break;
+            }
+            phi = next;
+        }
+
+        (self.lon0 + lambda.to_degrees(), phi.to_degrees())
+    }
+}
+
+/// Moves a coordinate between the Korean 1985 datum and GRS80. `inverse` is `false` from Bessel
+/// to GRS80 and `true` the other way around.
+fn shift_datum(lon: f64, lat: f64, from: &Ellipsoid, to: &Ellipsoid, inverse: bool) -> (f64, f64) {
+    const ARC_SECOND: f64 = PI / (180.0 * 3600.0);
+
+    let [tx, ty, tz, rx, ry, rz, s] = KOREAN_1985_TO_WGS84;
+    let (rx, ry, rz) = (rx * ARC_SECOND, ry * ARC_SECOND, rz * ARC_SECOND);
+    let scale = 1.0 + s * 1e-6;
+
+    let (x, y, z) = geodetic_to_geocentric(lon, lat, from);
+    let (x, y, z) = if inverse {
+        // The rotation matrix is orthogonal up to the square of the (tiny) angles, so its
+        // transpose is used as the inverse.
+        let (x, y, z) = ((x - tx) / scale, (y - ty) / scale, (z - tz) / scale);
+        (
+            x + rz * y - ry * z,
+            -rz * x + y + rx * z,
+            ry * x - rx * y + z,
+        )
+    } else {
+        (
+            tx + scale * (x - rz * y + ry * z),
+            ty + scale * (rz * x + y - rx * z),
+            tz + scale * (-ry * x + rx * y + z),
+        )
+    };
+    geocentric_to_geodetic(x, y, z, to)
+}
+
+/// Inverse of the Bessel to GRS80 shift, refined so that shifting the result back gives the
+/// original coordinate. The plain inverse transformation is off by a few millimeters because
+/// ellipsoidal heights are not carried between the datums.
+fn grs80_to_bessel(lon: f64, lat: f64) -> (f64, f64) {
+    let (mut bessel_lon, mut bessel_lat) = shift_datum(lon, lat, &GRS80, &BESSEL, true);
+    for _ in 0..3 {
+        let (grs80_lon, grs80_lat) = shift_datum(bessel_lon, bessel_lat, &BESSEL, &GRS80, false);
+        bessel_lon -= grs80_lon - lon;
+        bessel_lat -= grs80_lat - lat;
+    }
+    (bessel_lon, bessel_lat)
+}
+
+fn geodetic_to_geocentric(lon: f64, lat: f64, ellipsoid: &Ellipsoid) -> (f64, f64, f64) {
+    let e2 = ellipsoid.e2();
+    let (phi, lambda) = (lat.to_radians(), lon.to_radians());
+    let n = ellipsoid.a / (1.0 - e2 * phi.sin() * phi.sin()).sqrt();
+    (
+        n * phi.cos() * lambda.cos(),
+        n * phi.cos() * lambda.sin(),
+        n * (1.0 - e2) * phi.sin(),
+    )
+}
+
+fn geocentric_to_geodetic(x: f64, y: f64, z: f64, ellipsoid: &Ellipsoid) -> (f64, f64) {
+    let e2 = ellipsoid.e2();
+    let p = (x * x + y * y).sqrt();
+    let mut phi = (z / (p * (1.0 - e2))).atan();
+    for _ in 0..10 {
+        let n = ellipsoid.a / (1.0 - e2 * phi.sin() * phi.sin()).sqrt();
+        let h = p / phi.cos() - n;
+        let next = (z / (p * (1.0 - e2 * n / (n + h)))).atan();
+        if (next - phi).abs() < 1e-14 {
+            phi = next;
+            break;
+        }
+        phi = next;
+    }
+    (y.atan2(x).to_degrees(), phi.to_degrees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        grs80_to_bessel, shift_datum, to_wgs84_rect, transform, Ellipsoid, Projection,
+        Projection::*, TransverseMercator, BESSEL, GRS80,
+    };
+
+    const ALL: [Projection; 10] = [
+        Wgs84,
+        Wtm,
+        WCongnamul,
+        Tm,
+        Congnamul,
+        Korea2000West,
+        Korea2000Central,
+        Korea2000East,
+        Korea2000EastSea,
+        UtmK,
+    ];
+
+    fn assert_close((x, y): (f64, f64), (ex, ey): (f64, f64), tolerance: f64) {
+        assert!(
+            (x - ex).abs() < tolerance && (y - ey).abs() < tolerance,
+            "({}, {}) is not within {} of ({}, {})",
+            x,
+            y,
+            tolerance,
+            ex,
+            ey
+        );
+    }
+
+    #[test]
+    fn test_origins() {
+        assert_close(
+            Korea2000West.from_wgs84(125.0, 38.0),
+            (200000.0, 600000.0),
+            1e-6,
+        );
+        assert_close(
+            Korea2000Central.from_wgs84(127.0, 38.0),
+            (200000.0, 600000.0),
+            1e-6,
+        );
+        assert_close(
+            Korea2000East.from_wgs84(129.0, 38.0),
+            (200000.0, 600000.0),
+            1e-6,
+        );
+        assert_close(
+            Korea2000EastSea.from_wgs84(131.0, 38.0),
+            (200000.0, 600000.0),
+            1e-6,
+        );
+        assert_close(UtmK.from_wgs84(127.5, 38.0), (1000000.0, 2000000.0), 1e-6);
+        assert_close(Wtm.from_wgs84(127.0, 38.0), (200000.0, 500000.0), 1e-6);
+        assert_close(
+            WCongnamul.from_wgs84(127.0, 38.0),
+            (500000.0, 1250000.0),
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_reference_points() {
+        // Worked example of the transverse Mercator projection in EPSG Guidance Note 7-2 (OSGB 1936
+        // / British National Grid).
+        let tm = TransverseMercator {
+            ellipsoid: Ellipsoid {
+                a: 6377563.396,
+                f: 1.0 / 299.3249646,
+            },
+            lon0: -2.0,
+            lat0: 49.0,
+            k0: 0.9996012717,
+            false_easting: 400000.0,
+            false_northing: -100000.0,
+        };
+        assert_close(tm.forward(0.5, 50.5), (577274.99, 69740.50), 0.01);
+        assert_close(tm.inverse(577274.99, 69740.50), (0.5, 50.5), 1e-7);
+
+        // EPSG:5174 example of the proj4rs documentation.
+        assert_close(
+            Tm.to_wgs84(198236.32, 453407.856),
+            (126.98069676435814, 37.58308534678718),
+            1e-8,
+        );
+
+        // Seoul City Hall, Busan City Hall and Jeju, projected from the EPSG definitions with
+        // proj4rs 0.1.10. Its Bessel coordinates differ by a few millimeters because it carries
+        // ellipsoidal heights through the datum shift, which `grs80_to_bessel` drops to keep round
+        // trips exact.
+        let wgs84 = [(126.978, 37.5665), (129.0756, 35.1796), (126.5312, 33.4996)];
+        let points = [
+            (
+                UtmK,
+                0.001,
+                [
+                    (953901.1653, 1952032.0811),
+                    (1143467.3797, 1688281.9822),
+                    (910010.5457, 1501279.7890),
+                ],
+            ),
+            (
+                Korea2000West,
+                0.001,
+                [
+                    (374759.2927, 553724.3690),
+                    (571345.3345, 294636.9986),
+                    (342290.3255, 101709.6643),
+                ],
+            ),
+            (
+                Korea2000Central,
+                0.001,
+                [
+                    (198056.3667, 551885.0307),
+                    (389076.8036, 288993.7561),
+                    (156437.5196, 100758.4816),
+                ],
+            ),
+            (
+                Korea2000East,
+                0.001,
+                [
+                    (21352.8273, 553807.1444),
+                    (206886.2728, 287022.7229),
+                    (-29436.1917, 103389.3610),
+                ],
+            ),
+            (
+                Wtm,
+                0.001,
+                [
+                    (198056.3667, 451885.0307),
+                    (389076.8036, 188993.7561),
+                    (156437.5196, 758.4816),
+                ],
+            ),
+            (
+                Tm,
+                0.01,
+                [
+                    (197997.6081, 451567.1042),
+                    (389008.5856, 188663.7788),
+                    (156358.4902, 436.5792),
+                ],
+            ),
+        ];
+        for &(projection, tolerance, expected) in points.iter() {
+            for (&(lon, lat), &(x, y)) in wgs84.iter().zip(expected.iter()) {
+                assert_close(projection.from_wgs84(lon, lat), (x, y), tolerance);
+                assert_close(projection.to_wgs84(x, y), (lon, lat), tolerance * 1e-5);
+            }
+        }
+
+        // Kakao Pangyo office, WCONGNAMUL is WTM scaled by 2.5.
+        let (x, y) = Wtm.from_wgs84(127.1086228, 37.4012191);
+        assert_close(
+            WCongnamul.from_wgs84(127.1086228, 37.4012191),
+            (x * 2.5, y * 2.5),
+            1e-6,
+        );
+        // Korea 2000 central belt and UTM-K of the same point, about 39 km apart in easting.
+        let (x, y) = UtmK.from_wgs84(127.1086228, 37.4012191);
+        assert_close(
+            transform(UtmK, Korea2000Central, x, y),
+            Korea2000Central.from_wgs84(127.1086228, 37.4012191),
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_datum_shift() {
+        let (lon, lat) = grs80_to_bessel(127.0, 38.0);
+        assert_close(
+            shift_datum(lon, lat, &BESSEL, &GRS80, false),
+            (127.0, 38.0),
+            1e-8,
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &projection in ALL.iter() {
+            for &(lon, lat) in [(126.5, 33.3), (127.0276, 37.4979), (129.3, 35.1)].iter() {
+                let (x, y) = projection.from_wgs84(lon, lat);
+                assert_close(projection.to_wgs84(x, y), (lon, lat), 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rect() {
+        let (x1, y1) = Korea2000Central.from_wgs84(126.9, 37.4);
+        let (x2, y2) = Korea2000Central.from_wgs84(127.2, 37.7);
+        let (min_x, min_y, max_x, max_y) = to_wgs84_rect(Korea2000Central, x1, y1, x2, y2);
+
+        // Meridians converge away from the central one, so the enclosing box is slightly wider
+        // than the original corners.
+        assert!(min_x <= 126.9 + 1e-9 && max_x >= 127.2 - 1e-9);
+        assert!(min_y <= 37.4 + 1e-9 && max_y >= 37.7 - 1e-9);
+        assert!(max_x - min_x < 0.31 && max_y - min_y < 0.31);
+    }
+}
@@ -0,0 +1,191 @@
+use {
+    crate::Error,
+    std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Kakao resets daily quotas at midnight KST (UTC+9).
+const KST_OFFSET_SECS: u64 = 9 * 60 * 60;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Client-side token bucket that keeps an app key under Kakao's quotas.
+///
+/// Every HTTP request sent by a client takes one token, including retries. Clones share the same
+/// bucket and usage counter, so a single limiter can be handed to several clients that use the
+/// same app key.
+///
+/// When the bucket is empty the request either waits for the next token or, with
+/// [`fail_fast`](RateLimiter::fail_fast), fails with [`Error::RateLimited`]. Once the daily limit
+/// is used up every request fails until midnight KST.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    per_second: Option<f64>,
+    burst: Option<u32>,
+    daily_limit: Option<u64>,
+    fail_fast: bool,
+    state: Arc<Mutex<State>>,
+}
+
+/// Number of requests sent on a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DailyUsage {
+    /// Days since the Unix epoch, counted in KST.
+    pub day: u64,
+    pub count: u64,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: Option<f64>,
+    refilled_at: Instant,
+    usage: DailyUsage,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter {
+            per_second: None,
+            burst: None,
+            daily_limit: None,
+            fail_fast: false,
+            state: Arc::new(Mutex::new(State {
+                tokens: None,
+                refilled_at: Instant::now(),
+                usage: DailyUsage {
+                    day: today(),
+                    count: 0,
+                },
+            })),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// A limiter without any limit, which only counts the daily usage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sustained number of requests per second.
+    pub fn per_second(&mut self, per_second: u32) -> &mut Self {
+        self.per_second = Some(f64::from(per_second.max(1)));
+        self
+    }
+
+    /// Number of requests that can be sent at once after being idle. Defaults to the per-second
+    /// rate.
+    pub fn burst(&mut self, burst: u32) -> &mut Self {
+        self.burst = Some(burst.max(1));
+        self
+    }
+
+    pub fn daily_limit(&mut self, daily_limit: u64) -> &mut Self {
+        self.daily_limit = Some(daily_limit);
+        self
+    }
+
+    /// Fail with [`Error::RateLimited`] instead of waiting for a token.
+    pub fn fail_fast(&mut self, fail_fast: bool) -> &mut Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Usage of the current day.
+    pub fn usage(&self) -> DailyUsage {
+        let mut state = self.state.lock().unwrap();
+        state.roll_day();
+        state.usage
+    }
+
+    /// Restores a usage counter persisted from an earlier run. Counters of past days are ignored.
+    pub fn restore_usage(&self, usage: DailyUsage) {
+        let mut state = self.state.lock().unwrap();
+        state.roll_day();
+        if usage.day == state.usage.day {
+            state.usage.count = state.usage.count.max(usage.count);
+        }
+    }
+
+    fn capacity(&self) -> Option<f64> {
+        self.per_second
+            .map(|per_second| self.burst.map(f64::from).unwrap_or(per_second))
+    }
+
+    pub(crate) async fn acquire(&self) -> Result<(), Error> {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                state.roll_day();
+
+                if let Some(daily_limit) = self.daily_limit {
+                    if state.usage.count >= daily_limit {
+                        return Err(Error::RateLimited {
+                            retry_after: until_tomorrow(),
+                            daily: true,
+                        });
+                    }
+                }
+
+                match (self.per_second, self.capacity()) {
+                    (Some(per_second), Some(capacity)) => {
+                        let now = Instant::now();
+                        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+                        let tokens = state
+                            .tokens
+                            .map(|tokens| (tokens + elapsed * per_second).min(capacity))
+                            .unwrap_or(capacity);
+                        state.refilled_at = now;
+
+                        if tokens >= 1.0 {
+                            state.tokens = Some(tokens - 1.0);
+                            state.usage.count += 1;
+                            return Ok(());
+                        }
+                        state.tokens = Some(tokens);
+                        Duration::from_secs_f64((1.0 - tokens) / per_second)
+                    }
+                    _ => {
+                        state.usage.count += 1;
+                        return Ok(());
+                    }
+                }
+            };
+
+            if self.fail_fast {
+                return Err(Error::RateLimited {
+                    retry_after: wait,
+                    daily: false,
+                });
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl State {
+    fn roll_day(&mut self) {
+        let day = today();
+        if self.usage.day != day {
+            self.usage = DailyUsage { day, count: 0 };
+        }
+    }
+}
+
+fn kst_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+        + KST_OFFSET_SECS
+}
+
+fn today() -> u64 {
+    kst_now() / SECS_PER_DAY
+}
+
+fn until_tomorrow() -> Duration {
+    Duration::from_secs(SECS_PER_DAY - kst_now() % SECS_PER_DAY)
+}
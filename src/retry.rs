@@ -0,0 +1,138 @@
+use {
+    crate::Error,
+    rand::Rng,
+    reqwest::StatusCode,
+    std::{cmp, time::Duration},
+};
+
+/// When and how often a failed request is sent again.
+///
+/// Delays grow exponentially from `base_delay` and are capped at `max_delay`. With jitter
+/// enabled, the actual delay is picked uniformly between zero and the computed delay ("full
+/// jitter"), so that many workers failing at once do not retry in lockstep.
+///
+/// Only transient failures are retried: 5xx responses, `429 Too Many Requests` and transport
+/// failures such as timeouts or reset connections. Each class can be switched off. Errors
+/// outside of these classes, like an invalid app key or a schema mismatch, are returned
+/// immediately.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    retry_server_errors: bool,
+    retry_throttled: bool,
+    retry_transport: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            retry_server_errors: true,
+            retry_throttled: true,
+            retry_transport: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(&mut self, max_attempts: u32) -> &mut Self {
+        self.max_attempts = cmp::max(max_attempts, 1);
+        self
+    }
+
+    pub fn base_delay(&mut self, base_delay: Duration) -> &mut Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn max_delay(&mut self, max_delay: Duration) -> &mut Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn jitter(&mut self, jitter: bool) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn retry_server_errors(&mut self, retry: bool) -> &mut Self {
+        self.retry_server_errors = retry;
+        self
+    }
+
+    pub fn retry_throttled(&mut self, retry: bool) -> &mut Self {
+        self.retry_throttled = retry;
+        self
+    }
+
+    pub fn retry_transport(&mut self, retry: bool) -> &mut Self {
+        self.retry_transport = retry;
+        self
+    }
+
+    pub(crate) fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn is_retryable(&self, error: &Error) -> bool {
+        match error {
+            Error::Transport(e) => {
+                self.retry_transport
+                    && (e.is_timeout() || e.is_connect() || e.is_request() || e.is_body())
+            }
+            Error::Http(_) => self.retry_transport,
+            Error::Status { status, .. } => self.is_retryable_status(*status),
+            Error::Api(e) => self.is_retryable_status(e.status),
+            _ => false,
+        }
+    }
+
+    /// Whether `error` is a transient failure, a transport error, a server error or throttling,
+    /// whichever classes are switched on.
+    pub(crate) fn is_transient(error: &Error) -> bool {
+        match error {
+            Error::RetriesExhausted { source, .. } => Self::is_transient(source),
+            _ => Self::default().is_retryable(error),
+        }
+    }
+
+    fn is_retryable_status(&self, status: StatusCode) -> bool {
+        (self.retry_server_errors && status.is_server_error())
+            || (self.retry_throttled && status == StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Delay before the attempt following the `attempt`th one.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        let delay = cmp::min(
+            self.base_delay
+                .checked_mul(factor)
+                .unwrap_or(self.max_delay),
+            self.max_delay,
+        );
+        if self.jitter {
+            delay.mul_f64(rand::thread_rng().gen::<f64>())
+        } else {
+            delay
+        }
+    }
+}
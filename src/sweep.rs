@@ -0,0 +1,243 @@
+//! Exhaustive search over a bounding box.
+//!
+//! Kakao serves at most 45 pages of 15 places for a query, so a category or keyword search over a
+//! large area silently drops everything beyond the first 675 places. A [`Sweep`] works around the
+//! cap by splitting the area into quadrants whenever a tile reports more places (`total_count`)
+//! than it can page through (`pageable_count`), and merging the places of all tiles.
+//!
+//! ```no_run
+//! # #[allow(non_snake_case)]
+//! # async fn foo() {
+//! # let APP_KEY = "";
+//! let client = daummap::KakaoClient::new(APP_KEY);
+//! let result = daummap::sweep::Sweep::category(
+//!     &client,
+//!     daummap::CategoryGroup::Pharmacy,
+//!     126.9,
+//!     37.4,
+//!     127.2,
+//!     37.7,
+//! )
+//! .run()
+//! .await
+//! .unwrap();
+//! println!("{} pharmacies, {:?}", result.places.len(), result.stats);
+//! # }
+//! ```
+
+use {
+    crate::{CategoryGroup, Error, KakaoClient, Place},
+    std::collections::HashSet,
+};
+
+/// Page size used for every tile, the largest one Kakao accepts for place searches.
+const TILE_SIZE: usize = 15;
+
+#[derive(Debug, Clone)]
+enum Target {
+    Category(CategoryGroup),
+    Keyword(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Sweep<'a> {
+    client: &'a KakaoClient,
+    target: Target,
+    rect: (f64, f64, f64, f64),
+    max_depth: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    /// Places of every tile, de-duplicated by id.
+    pub places: Vec<Place>,
+    pub stats: SweepStats,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SweepStats {
+    /// Number of tiles queried, including the ones that were split.
+    pub tiles: usize,
+    /// Number of tiles split into quadrants.
+    pub splits: usize,
+    /// Deepest level reached, the whole bounding box being level 0.
+    pub depth: usize,
+    /// Number of API calls made.
+    pub requests: usize,
+    /// Number of tiles that still hit the page cap at the maximum depth. Places of these tiles
+    /// may be missing from the result.
+    pub truncated_tiles: usize,
+    /// Number of places returned by more than one tile.
+    pub duplicates: usize,
+    /// Fraction of the bounding box covered by tiles whose places were fetched completely.
+    pub coverage: f64,
+}
+
+struct Tile {
+    rect: (f64, f64, f64, f64),
+    depth: usize,
+}
+
+struct TileResponse {
+    places: Vec<Place>,
+    total_count: usize,
+    pageable_count: usize,
+    is_end: bool,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum PlaceKey {
+    Id(usize),
+    Fallback(String, Option<u64>, Option<u64>),
+}
+
+impl<'a> Sweep<'a> {
+    pub fn category(
+        client: &'a KakaoClient,
+        category_group: CategoryGroup,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Self {
+        Sweep::new(client, Target::Category(category_group), (x1, y1, x2, y2))
+    }
+
+    pub fn keyword(
+        client: &'a KakaoClient,
+        query: &str,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Self {
+        Sweep::new(client, Target::Keyword(query.to_string()), (x1, y1, x2, y2))
+    }
+
+    fn new(client: &'a KakaoClient, target: Target, rect: (f64, f64, f64, f64)) -> Self {
+        Sweep {
+            client,
+            target,
+            rect,
+            max_depth: 8,
+        }
+    }
+
+    /// How many times a tile may be split. Defaults to 8, tiles of 1/65536 of the area.
+    pub fn max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub async fn run(&self) -> Result<SweepResult, Error> {
+        let mut stats = SweepStats::default();
+        let mut seen = HashSet::new();
+        let mut places = Vec::new();
+        let mut tiles = vec![Tile {
+            rect: self.rect,
+            depth: 0,
+        }];
+
+        while let Some(tile) = tiles.pop() {
+            stats.tiles += 1;
+            stats.requests += 1;
+            stats.depth = stats.depth.max(tile.depth);
+
+            let first = self.fetch(tile.rect, 1).await?;
+            let truncated = first.total_count > first.pageable_count;
+            if truncated && tile.depth < self.max_depth {
+                stats.splits += 1;
+                tiles.extend(split(tile.rect).iter().map(|&rect| Tile {
+                    rect,
+                    depth: tile.depth + 1,
+                }));
+                continue;
+            }
+            if truncated {
+                stats.truncated_tiles += 1;
+            } else {
+                stats.coverage += 0.25f64.powi(tile.depth as i32);
+            }
+
+            let last_page = first.pageable_count.div_ceil(TILE_SIZE);
+            let mut is_end = first.is_end;
+            let mut tile_places = first.places;
+            let mut page = 2;
+            while !is_end && page <= last_page {
+                let resp = self.fetch(tile.rect, page).await?;
+                stats.requests += 1;
+                is_end = resp.is_end;
+                tile_places.extend(resp.places);
+                page += 1;
+            }
+
+            for place in tile_places {
+                if seen.insert(place_key(&place)) {
+                    places.push(place);
+                } else {
+                    stats.duplicates += 1;
+                }
+            }
+        }
+
+        Ok(SweepResult { places, stats })
+    }
+
+    async fn fetch(&self, rect: (f64, f64, f64, f64), page: usize) -> Result<TileResponse, Error> {
+        let (x1, y1, x2, y2) = rect;
+        Ok(match self.target {
+            Target::Category(ref category_group) => {
+                let resp = self
+                    .client
+                    .category_rect(category_group.clone(), x1, y1, x2, y2)
+                    .page(page)
+                    .size(TILE_SIZE)
+                    .get()
+                    .await?;
+                TileResponse {
+                    places: resp.places,
+                    total_count: resp.total_count,
+                    pageable_count: resp.pageable_count,
+                    is_end: resp.is_end,
+                }
+            }
+            Target::Keyword(ref query) => {
+                let resp = self
+                    .client
+                    .keyword(query)
+                    .rect(x1, y1, x2, y2)
+                    .page(page)
+                    .size(TILE_SIZE)
+                    .get()
+                    .await?;
+                TileResponse {
+                    places: resp.places,
+                    total_count: resp.total_count,
+                    pageable_count: resp.pageable_count,
+                    is_end: resp.is_end,
+                }
+            }
+        })
+    }
+}
+
+fn split((x1, y1, x2, y2): (f64, f64, f64, f64)) -> [(f64, f64, f64, f64); 4] {
+    let (mx, my) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+    [
+        (x1, y1, mx, my),
+        (mx, y1, x2, my),
+        (x1, my, mx, y2),
+        (mx, my, x2, y2),
+    ]
+}
+
+fn place_key(place: &Place) -> PlaceKey {
+    match place.id {
+        Some(id) => PlaceKey::Id(id),
+        None => PlaceKey::Fallback(
+            place.name.clone(),
+            place.longitude.map(f64::to_bits),
+            place.latitude.map(f64::to_bits),
+        ),
+    }
+}
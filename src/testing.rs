@@ -0,0 +1,462 @@
+//! A mock Kakao Local API for tests.
+//!
+//! [`MockKakao`] is an HTTP server bound to an ephemeral port of the loopback interface, so any
+//! number of tests can run one in parallel. It serves the bundled [`fixtures`] for every
+//! endpoint, or rules set up with [`on`](MockKakao::on), and keeps every request it received for
+//! assertions. Requests without a `KakaoAK` authorization header are rejected like Kakao does.
+//!
+//! ```no_run
+//! # async fn foo() {
+//! use daummap::testing::MockKakao;
+//!
+//! let mock = MockKakao::start().await;
+//! mock.on("/search/keyword.json")
+//!     .param("query", "없는 곳")
+//!     .respond(r#"{ "meta": { "total_count": 0, "pageable_count": 0, "is_end": true }, "documents": [] }"#);
+//!
+//! let client = mock.client("key");
+//! let resp = client.keyword("카카오프렌즈").get().await.unwrap();
+//! assert_eq!(resp.places[0].name, "카카오프렌즈 코엑스점");
+//! mock.assert_received("/search/keyword.json", &[("query", "카카오프렌즈")]);
+//! # }
+//! ```
+//!
+//! Fixtures of live responses are captured with [`record`](MockKakao::record), which forwards
+//! requests to Kakao and saves each successful response, and served again with
+//! [`replay`](MockKakao::replay).
+
+use {
+    crate::{
+        cache::{CacheKey, CacheStore, CachedResponse, FileStore},
+        KakaoClient, KakaoClientBuilder, KAKAO_LOCAL_API_BASE_URL,
+    },
+    hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server, StatusCode,
+    },
+    std::{
+        convert::Infallible,
+        fmt,
+        net::SocketAddr,
+        path::Path,
+        sync::{Arc, Mutex},
+        time::SystemTime,
+    },
+    tokio::sync::oneshot,
+};
+
+/// Responses served by [`MockKakao::start`], taken from the Kakao documentation.
+pub mod fixtures {
+    /// `/search/address.json`: "전북 삼성동 100".
+    pub const ADDRESS: &str = include_str!("testing/fixtures/address.json");
+    /// `/search/keyword.json`: "카카오프렌즈".
+    pub const KEYWORD: &str = include_str!("testing/fixtures/keyword.json");
+    /// `/search/category.json`: pharmacies.
+    pub const CATEGORY: &str = include_str!("testing/fixtures/category.json");
+    /// `/geo/coord2regioncode.json`: a legal and an administrative region.
+    pub const COORD2REGIONCODE: &str = include_str!("testing/fixtures/coord2regioncode.json");
+    /// `/geo/coord2address.json`: a land-lot and a road address.
+    pub const COORD2ADDRESS: &str = include_str!("testing/fixtures/coord2address.json");
+    /// `/geo/transcoord.json`.
+    pub const TRANSCOORD: &str = include_str!("testing/fixtures/transcoord.json");
+}
+
+/// A mock Kakao Local API server, shut down when dropped.
+pub struct MockKakao {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    source: Arc<Source>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+/// A rule being set up with [`MockKakao::on`].
+#[must_use = "a rule is only added by `respond` or `respond_with`"]
+pub struct Rule<'a> {
+    mock: &'a MockKakao,
+    path: String,
+    params: Vec<(String, String)>,
+    status: StatusCode,
+    times: Option<usize>,
+}
+
+/// A request received by a [`MockKakao`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedRequest {
+    pub path: String,
+    /// The raw query string.
+    pub query: String,
+    /// The decoded query parameters, in order.
+    pub params: Vec<(String, String)>,
+    /// The key of the `KakaoAK` authorization header.
+    pub app_key: Option<String>,
+}
+
+type Handler = dyn Fn(&ReceivedRequest) -> String + Send + Sync;
+
+struct State {
+    rules: Vec<StoredRule>,
+    requests: Vec<ReceivedRequest>,
+    app_key: Option<String>,
+}
+
+struct StoredRule {
+    path: String,
+    params: Vec<(String, String)>,
+    status: StatusCode,
+    remaining: Option<usize>,
+    handler: Arc<Handler>,
+}
+
+enum Source {
+    Fixtures,
+    Replay(FileStore),
+    Record {
+        store: FileStore,
+        upstream: String,
+        http: reqwest::Client,
+    },
+}
+
+impl MockKakao {
+    /// Starts a server answering with the bundled [`fixtures`].
+    pub async fn start() -> Self {
+        Self::with_source(Source::Fixtures)
+    }
+
+    /// Starts a server answering with the responses recorded in `dir`. Requests that were not
+    /// recorded are answered with 404.
+    pub async fn replay(dir: impl AsRef<Path>) -> Self {
+        let store = FileStore::new(dir).expect("cannot open the fixture directory");
+        Self::with_source(Source::Replay(store))
+    }
+
+    /// Starts a server forwarding requests to the real API and recording every successful
+    /// response to `dir`, to be replayed with [`replay`](Self::replay). The app key of the
+    /// forwarded requests is not recorded.
+    pub async fn record(dir: impl AsRef<Path>) -> Self {
+        Self::record_from(dir, KAKAO_LOCAL_API_BASE_URL).await
+    }
+
+    /// Same as [`record`](Self::record), forwarding to `upstream` instead of Kakao.
+    pub async fn record_from(dir: impl AsRef<Path>, upstream: &str) -> Self {
+        let store = FileStore::new(dir).expect("cannot open the fixture directory");
+        Self::with_source(Source::Record {
+            store,
+            upstream: upstream.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    fn with_source(source: Source) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            rules: Vec::new(),
+            requests: Vec::new(),
+            app_key: None,
+        }));
+        let source = Arc::new(source);
+
+        let service = {
+            let (state, source) = (state.clone(), source.clone());
+            make_service_fn(move |_| {
+                let (state, source) = (state.clone(), source.clone());
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let (state, source) = (state.clone(), source.clone());
+                        async move { Ok::<_, Infallible>(handle(&state, &source, req).await) }
+                    }))
+                }
+            })
+        };
+
+        let (shutdown, shutdown_receiver) = oneshot::channel::<()>();
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .expect("cannot bind the mock server")
+            .serve(service);
+        let addr = server.local_addr();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            shutdown_receiver.await.ok();
+        }));
+
+        MockKakao {
+            addr,
+            state,
+            source,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    /// Base URL of the server, to be passed to
+    /// [`KakaoClientBuilder::base_url`](crate::KakaoClientBuilder::base_url).
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client builder pointed at the server.
+    pub fn builder(&self, app_key: &str) -> KakaoClientBuilder {
+        let mut builder = KakaoClient::builder(app_key);
+        builder.base_url(&self.url());
+        builder
+    }
+
+    /// A client pointed at the server.
+    pub fn client(&self, app_key: &str) -> KakaoClient {
+        self.builder(app_key)
+            .build()
+            .expect("cannot build the client")
+    }
+
+    /// Rejects requests with another app key, like Kakao rejects unknown keys. Any key is
+    /// accepted by default.
+    pub fn expect_app_key(&self, app_key: &str) -> &Self {
+        self.state.lock().unwrap().app_key = Some(app_key.to_string());
+        self
+    }
+
+    /// Sets up the response to requests to `path`. Rules set up later take precedence, so
+    /// general rules go first.
+    pub fn on(&self, path: &str) -> Rule<'_> {
+        Rule {
+            mock: self,
+            path: normalize(path),
+            params: Vec::new(),
+            status: StatusCode::OK,
+            times: None,
+        }
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Every request received so far, forgetting them.
+    pub fn take_requests(&self) -> Vec<ReceivedRequest> {
+        std::mem::take(&mut self.state.lock().unwrap().requests)
+    }
+
+    /// Panics unless a request to `path` with all of `params` was received.
+    #[track_caller]
+    pub fn assert_received(&self, path: &str, params: &[(&str, &str)]) {
+        let path = normalize(path);
+        let requests = self.requests();
+        let found = requests.iter().any(|req| {
+            req.path == path
+                && params
+                    .iter()
+                    .all(|(name, value)| req.param(name) == Some(*value))
+        });
+        assert!(
+            found,
+            "no request to {} with {:?}, received: {:#?}",
+            path, params, requests
+        );
+    }
+}
+
+impl Drop for MockKakao {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl fmt::Debug for MockKakao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match *self.source {
+            Source::Fixtures => "fixtures",
+            Source::Replay(_) => "replay",
+            Source::Record { .. } => "record",
+        };
+        f.debug_struct("MockKakao")
+            .field("addr", &self.addr)
+            .field("source", &source)
+            .finish()
+    }
+}
+
+impl<'a> Rule<'a> {
+    /// Only matches requests with the parameter.
+    pub fn param(&mut self, name: &str, value: &str) -> &mut Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Status of the response. Defaults to 200.
+    pub fn status(&mut self, status: StatusCode) -> &mut Self {
+        self.status = status;
+        self
+    }
+
+    /// Answers only the first `times` matching requests; later ones fall through to the other
+    /// rules.
+    pub fn times(&mut self, times: usize) -> &mut Self {
+        self.times = Some(times);
+        self
+    }
+
+    pub fn respond(&mut self, body: &str) {
+        let body = body.to_string();
+        self.respond_with(move |_| body.clone());
+    }
+
+    /// Builds the body of each response from its request.
+    pub fn respond_with(
+        &mut self,
+        handler: impl Fn(&ReceivedRequest) -> String + Send + Sync + 'static,
+    ) {
+        self.mock.state.lock().unwrap().rules.push(StoredRule {
+            path: self.path.clone(),
+            params: self.params.clone(),
+            status: self.status,
+            remaining: self.times,
+            handler: Arc::new(handler),
+        });
+    }
+}
+
+impl ReceivedRequest {
+    /// Value of the query parameter `name`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+async fn handle(state: &Mutex<State>, source: &Source, req: Request<Body>) -> Response<Body> {
+    let query = req.uri().query().unwrap_or_default().to_string();
+    let received = ReceivedRequest {
+        path: req.uri().path().to_string(),
+        params: url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect(),
+        query,
+        app_key: req
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("KakaoAK "))
+            .map(str::to_string),
+    };
+
+    let handler = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(received.clone());
+
+        let authorized = match (&received.app_key, &state.app_key) {
+            (Some(key), Some(expected)) => key == expected,
+            (key, _) => key.is_some(),
+        };
+        if !authorized {
+            return response(
+                StatusCode::UNAUTHORIZED,
+                r#"{"errorType":"AccessDeniedError","message":"cannot find appKey"}"#.to_string(),
+            );
+        }
+
+        state
+            .rules
+            .iter_mut()
+            .rev()
+            .filter(|rule| rule.remaining != Some(0))
+            .find(|rule| {
+                rule.path == received.path
+                    && rule
+                        .params
+                        .iter()
+                        .all(|(name, value)| received.param(name) == Some(value.as_str()))
+            })
+            .map(|rule| {
+                if let Some(remaining) = rule.remaining.as_mut() {
+                    *remaining -= 1;
+                }
+                (rule.status, rule.handler.clone())
+            })
+    };
+    if let Some((status, handler)) = handler {
+        return response(status, handler(&received));
+    }
+
+    // Recordings are keyed as Kakao responses whatever the upstream, so they replay on any port.
+    let key = CacheKey::new(
+        KAKAO_LOCAL_API_BASE_URL,
+        &received.path,
+        &borrowed(&received.params),
+    );
+    match source {
+        Source::Fixtures => match fixture(&received.path) {
+            Some(body) => response(StatusCode::OK, body.to_string()),
+            None => not_found(&received),
+        },
+        Source::Replay(store) => match store.get(&key) {
+            Ok(Some(cached)) => response(StatusCode::OK, cached.body),
+            _ => not_found(&received),
+        },
+        Source::Record {
+            store,
+            upstream,
+            http,
+        } => {
+            let url = format!("{}{}?{}", upstream, received.path, received.query);
+            let app_key = received.app_key.clone().unwrap_or_default();
+            let resp = http
+                .get(&url)
+                .header("Authorization", format!("KakaoAK {}", app_key))
+                .send()
+                .await;
+            let (status, body) = match resp {
+                Ok(resp) => {
+                    let status = resp.status();
+                    (status, resp.text().await.unwrap_or_default())
+                }
+                Err(e) => (StatusCode::BAD_GATEWAY, e.to_string()),
+            };
+            if status.is_success() {
+                let cached = CachedResponse {
+                    body: body.clone(),
+                    stored_at: SystemTime::now(),
+                };
+                store.put(&key, &cached).expect("cannot write the fixture");
+            }
+            response(status, body)
+        }
+    }
+}
+
+fn fixture(path: &str) -> Option<&'static str> {
+    match path {
+        "/search/address.json" => Some(fixtures::ADDRESS),
+        "/search/keyword.json" => Some(fixtures::KEYWORD),
+        "/search/category.json" => Some(fixtures::CATEGORY),
+        "/geo/coord2regioncode.json" => Some(fixtures::COORD2REGIONCODE),
+        "/geo/coord2address.json" => Some(fixtures::COORD2ADDRESS),
+        "/geo/transcoord.json" => Some(fixtures::TRANSCOORD),
+        _ => None,
+    }
+}
+
+fn not_found(req: &ReceivedRequest) -> Response<Body> {
+    response(
+        StatusCode::NOT_FOUND,
+        format!("no fixture for {}?{}", req.path, req.query),
+    )
+}
+
+fn response(status: StatusCode, body: String) -> Response<Body> {
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = status;
+    resp
+}
+
+fn normalize(path: &str) -> String {
+    format!("/{}", path.trim_start_matches('/'))
+}
+
+fn borrowed(params: &[(String, String)]) -> Vec<(&str, String)> {
+    params
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect()
+}
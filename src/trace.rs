@@ -0,0 +1,294 @@
+//! `tracing` spans of API calls.
+//!
+//! Every call gets a `daummap.request` span recording the endpoint path, the `page` and `size`
+//! parameters, the number of documents returned and their `total_count`, the HTTP status of the
+//! last attempt, the latency in milliseconds, the number of attempts and how the cache answered.
+//! The query text is recorded only when enabled with
+//! [`KakaoClientBuilder::trace_queries`](crate::KakaoClientBuilder::trace_queries). The app key
+//! is never recorded.
+
+use {
+    crate::{Documents, Error, KakaoClient},
+    std::{future::Future, time::Instant},
+    tracing::{field, Instrument},
+};
+
+pub(crate) async fn instrument<T>(
+    client: &KakaoClient,
+    path: &str,
+    params: &[(&str, String)],
+    fut: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    let span = tracing::info_span!(
+        "daummap.request",
+        path,
+        page = field::Empty,
+        size = field::Empty,
+        query = field::Empty,
+        documents = field::Empty,
+        total_count = field::Empty,
+        status = field::Empty,
+        latency_ms = field::Empty,
+        attempts = field::Empty,
+        cache = field::Empty,
+        error = field::Empty,
+    );
+    for (name, value) in params {
+        match *name {
+            "page" | "size" => {
+                if let Ok(value) = value.parse::<u64>() {
+                    span.record(*name, value);
+                }
+            }
+            "query" if client.trace_queries => {
+                span.record("query", value.as_str());
+            }
+            _ => {}
+        }
+    }
+
+    let started = Instant::now();
+    let result = fut.instrument(span.clone()).await;
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    if let Err(ref e) = result {
+        span.record("error", describe(e).as_str());
+    }
+    result
+}
+
+/// The error without anything that may contain the query: the URL, the response body or the
+/// parser message quoting it.
+pub(crate) fn describe(e: &Error) -> String {
+    match e {
+        Error::Transport(e) => {
+            let message = e.to_string();
+            match e.url() {
+                Some(url) => message.replace(&format!(" for url ({})", url), ""),
+                None => message,
+            }
+        }
+        Error::Http(_) => "Transport error".to_string(),
+        Error::Status { status, .. } => format!("HTTP status {}", status),
+        Error::Api(e) => {
+            let mut message = format!("Kakao API error ({})", e.status);
+            if let Some(ref error_type) = e.error_type {
+                message += &format!(" {}", error_type);
+            }
+            if let Some(code) = e.code {
+                message += &format!(" [{}]", code);
+            }
+            message
+        }
+        Error::Deserialize { source, .. } => format!(
+            "Cannot deserialize response at line {} column {}",
+            source.line(),
+            source.column()
+        ),
+        Error::ParseCategoryGroup(_) => "Cannot parse category group".to_string(),
+        Error::ParseCoordSystem(_) => "Cannot parse coordinate system".to_string(),
+        Error::RetriesExhausted { attempts, source } => {
+            format!(
+                "Giving up after {} attempts: {}",
+                attempts,
+                describe(source)
+            )
+        }
+        Error::Url(_) | Error::RateLimited { .. } | Error::CacheMiss { .. } => e.to_string(),
+    }
+}
+
+/// Records the size of a response on the current span.
+pub(crate) fn record_documents(resp: &impl Documents) {
+    let span = tracing::Span::current();
+    if let Some(documents) = resp.document_count() {
+        span.record("documents", documents as u64);
+    }
+    if let Some(total_count) = resp.total_count() {
+        span.record("total_count", total_count as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{
+            testing::fixtures, transport::MemoryTransport, KakaoClient, ResponseCache, RetryPolicy,
+        },
+        reqwest::StatusCode,
+        std::{
+            collections::HashMap,
+            fmt,
+            sync::{Arc, Mutex},
+            time::Duration,
+        },
+        tracing::{
+            field::{Field, Visit},
+            span::{Attributes, Id, Record},
+            Event, Metadata, Subscriber,
+        },
+        tracing_core::span::Current,
+    };
+
+    type Spans = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+    #[derive(Default)]
+    struct Recorder {
+        spans: Spans,
+        metadata: Mutex<Vec<&'static Metadata<'static>>>,
+        entered: Mutex<Vec<Id>>,
+    }
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            let mut fields = HashMap::new();
+            attrs.record(&mut Fields(&mut fields));
+            spans.push(fields);
+            self.metadata.lock().unwrap().push(attrs.metadata());
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1]));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            match self.entered.lock().unwrap().last() {
+                Some(span) => {
+                    let metadata = self.metadata.lock().unwrap()[span.into_u64() as usize - 1];
+                    Current::new(span.clone(), metadata)
+                }
+                None => Current::none(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spans() {
+        let recorder = Recorder::default();
+        let spans = recorder.spans.clone();
+        let _guard = tracing::subscriber::set_default(recorder);
+
+        let transport = MemoryTransport::new();
+        transport.respond("/search/keyword.json", fixtures::KEYWORD);
+        transport.respond("/geo/coord2regioncode.json", r#"{ "documents": [] }"#);
+        let mut builder = KakaoClient::builder("secret");
+        builder.transport(transport).cache(ResponseCache::new(10));
+        let client = builder.build().unwrap();
+
+        client.keyword("우리집").size(2).get().await.unwrap();
+        client.keyword("우리집").size(2).get().await.unwrap();
+        client.coord(127.0, 37.0).get_region().await.unwrap();
+        client.address("우리집").get().await.unwrap_err();
+
+        let client = builder.trace_queries(true).build().unwrap();
+        client.keyword("우리집").no_cache().get().await.unwrap();
+
+        let mut retry = RetryPolicy::new();
+        retry.max_attempts(2).base_delay(Duration::from_millis(1));
+        let client = KakaoClient::builder("secret")
+            .base_url("http://127.0.0.1:1")
+            .retry(retry)
+            .build()
+            .unwrap();
+        client.keyword("우리집").get().await.unwrap_err();
+
+        let transport = MemoryTransport::new();
+        transport.respond("/search/keyword.json", fixtures::KEYWORD);
+        let mut cache = ResponseCache::new(10);
+        cache.refresh_if_older_than(Duration::ZERO);
+        let client = KakaoClient::builder("secret")
+            .transport(transport.clone())
+            .retry(RetryPolicy::none())
+            .cache(cache)
+            .build()
+            .unwrap();
+        client.keyword("우리집").get().await.unwrap();
+        transport.respond_with_status(
+            "/search/keyword.json",
+            StatusCode::SERVICE_UNAVAILABLE,
+            "unavailable",
+        );
+        client.keyword("우리집").get().await.unwrap();
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 8);
+        assert!(spans
+            .iter()
+            .flat_map(HashMap::values)
+            .all(|value| !value.contains("secret")));
+
+        let span = &spans[0];
+        assert_eq!(span["path"], "search/keyword.json");
+        assert_eq!(span["page"], "1");
+        assert_eq!(span["size"], "2");
+        assert_eq!(span["documents"], "1");
+        assert_eq!(span["total_count"], "14");
+        assert_eq!(span["status"], "200");
+        assert_eq!(span["attempts"], "1");
+        assert_eq!(span["cache"], "miss");
+        assert!(span.contains_key("latency_ms"));
+        assert!(!span.contains_key("query"));
+
+        let span = &spans[1];
+        assert_eq!(span["cache"], "hit");
+        assert_eq!(span["documents"], "1");
+        assert!(!span.contains_key("status"));
+        assert!(!span.contains_key("attempts"));
+
+        let span = &spans[2];
+        assert_eq!(span["path"], "geo/coord2regioncode.json");
+        assert_eq!(span["documents"], "0");
+        assert!(!span.contains_key("total_count"));
+
+        let span = &spans[3];
+        assert_eq!(span["status"], "404");
+        assert!(span.contains_key("error"));
+        assert!(!span.contains_key("documents"));
+
+        let span = &spans[4];
+        assert_eq!(span["query"], "우리집");
+        assert_eq!(span["cache"], "disabled");
+
+        let span = &spans[5];
+        assert!(span["error"].starts_with("Giving up after 2 attempts: error sending request"));
+        assert!(!span["error"].contains("query"));
+        assert!(!span["error"].contains("%EC%9A%B0"));
+
+        let span = &spans[7];
+        assert_eq!(span["cache"], "stale_fallback");
+        assert_eq!(span["status"], "503");
+        assert_eq!(span["error"], "HTTP status 503 Service Unavailable");
+        assert_eq!(span["documents"], "1");
+    }
+}
@@ -0,0 +1,188 @@
+use {
+    crate::{request, Documents, Error, KakaoClient},
+    serde::Deserialize,
+    std::{borrow::Cow, fmt, str::FromStr},
+    thiserror::Error as ThisError,
+};
+
+/// Coordinate systems understood by Kakao.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoordSystem {
+    Wgs84,
+    WCongnamul,
+    Congnamul,
+    Wtm,
+    Tm,
+    Ktm,
+    Utm,
+    Bessel,
+    Wktm,
+    Wutm,
+}
+
+impl CoordSystem {
+    pub fn to_code<'a>(self) -> &'a str {
+        use crate::CoordSystem::*;
+        match self {
+            Wgs84 => "WGS84",
+            WCongnamul => "WCONGNAMUL",
+            Congnamul => "CONGNAMUL",
+            Wtm => "WTM",
+            Tm => "TM",
+            Ktm => "KTM",
+            Utm => "UTM",
+            Bessel => "BESSEL",
+            Wktm => "WKTM",
+            Wutm => "WUTM",
+        }
+    }
+}
+
+impl FromStr for CoordSystem {
+    type Err = ParseCoordSystem;
+
+    fn from_str(s: &str) -> Result<Self, ParseCoordSystem> {
+        use crate::CoordSystem::*;
+
+        Ok(match s {
+            "WGS84" => Wgs84,
+            "WCONGNAMUL" => WCongnamul,
+            "CONGNAMUL" => Congnamul,
+            "WTM" => Wtm,
+            "TM" => Tm,
+            "KTM" => Ktm,
+            "UTM" => Utm,
+            "BESSEL" => Bessel,
+            "WKTM" => Wktm,
+            "WUTM" => Wutm,
+            _ => return Err(ParseCoordSystem(s.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for CoordSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_code())
+    }
+}
+
+/// Serialized as its Kakao code, e.g. `"WGS84"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CoordSystem {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_code())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CoordSystem {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, ThisError)]
+#[error("Cannot parse coordinate system from {0}")]
+pub struct ParseCoordSystem(pub String);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransCoord {
+    pub x: f64,
+    pub y: f64,
+    pub coord_system: CoordSystem,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransCoordRequest<'a> {
+    client: Cow<'a, KakaoClient>,
+    x: f64,
+    y: f64,
+    input_coord: CoordSystem,
+    output_coord: CoordSystem,
+}
+
+impl TransCoordRequest<'static> {
+    pub fn new(app_key: &str, x: f64, y: f64) -> Self {
+        TransCoordRequest::with_client(Cow::Owned(KakaoClient::new(app_key)), x, y)
+    }
+}
+
+impl<'a> TransCoordRequest<'a> {
+    pub(crate) fn with_client(client: Cow<'a, KakaoClient>, x: f64, y: f64) -> Self {
+        TransCoordRequest {
+            client,
+            x,
+            y,
+            input_coord: CoordSystem::Wgs84,
+            output_coord: CoordSystem::Wgs84,
+        }
+    }
+
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.client.to_mut().base_url = base_url.to_string();
+        self
+    }
+
+    /// Sends the request even if the client's cache holds a response, and leaves the cache as
+    /// is.
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.client.to_mut().cache = None;
+        self
+    }
+
+    /// Coordinate system of the given coordinate. Defaults to WGS84.
+    pub fn input_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.input_coord = coord_system;
+        self
+    }
+
+    /// Coordinate system to convert to. Defaults to WGS84.
+    pub fn output_coord(&mut self, coord_system: CoordSystem) -> &mut Self {
+        self.output_coord = coord_system;
+        self
+    }
+
+    pub async fn get(&self) -> Result<Vec<TransCoord>, Error> {
+        static API_PATH: &str = "/geo/transcoord.json";
+
+        let resp = request::<RawResponse>(
+            &self.client,
+            API_PATH,
+            &[
+                ("x", self.x.to_string()),
+                ("y", self.y.to_string()),
+                ("input_coord", self.input_coord.to_string()),
+                ("output_coord", self.output_coord.to_string()),
+            ],
+        )
+        .await?;
+        Ok(resp
+            .documents
+            .into_iter()
+            .map(|rcoord| TransCoord {
+                x: rcoord.x,
+                y: rcoord.y,
+                coord_system: self.output_coord,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawResponse {
+    documents: Vec<RawCoord>,
+}
+
+impl Documents for RawResponse {
+    fn document_count(&self) -> Option<usize> {
+        Some(self.documents.len())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCoord {
+    x: f64,
+    y: f64,
+}
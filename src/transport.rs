@@ -0,0 +1,154 @@
+//! The HTTP layer under the request builders.
+//!
+//! Every request sent by a [`KakaoClient`](crate::KakaoClient) goes through its [`Transport`],
+//! [`ReqwestTransport`] unless another one is set with
+//! [`KakaoClientBuilder::transport`](crate::KakaoClientBuilder::transport). A
+//! [`MemoryTransport`] answers with canned responses without opening any socket.
+//!
+//! ```
+//! # async fn foo() {
+//! use daummap::transport::MemoryTransport;
+//!
+//! let transport = MemoryTransport::new();
+//! transport.respond(
+//!     "/search/keyword.json",
+//!     r#"{ "meta": { "total_count": 0, "pageable_count": 0, "is_end": true }, "documents": [] }"#,
+//! );
+//! let client = daummap::KakaoClient::builder("key")
+//!     .transport(transport.clone())
+//!     .build()
+//!     .unwrap();
+//! let resp = client.keyword("카카오프렌즈").get().await.unwrap();
+//! assert_eq!(resp.total_count, 0);
+//! assert_eq!(transport.requests().len(), 1);
+//! # }
+//! ```
+
+use {
+    crate::Error,
+    futures::future::BoxFuture,
+    reqwest::{Client, StatusCode, Url},
+    std::{
+        fmt,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Sends HTTP requests for a client.
+pub trait Transport: fmt::Debug + Send + Sync {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>>;
+}
+
+/// A GET request to the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+/// Transport over a pooled `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+/// Transport answering with canned responses, matched by the end of the URL path.
+///
+/// Clones share the same responses and recorded requests. Requests matching no response are
+/// answered with 404.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    responses: Vec<(String, HttpResponse)>,
+    requests: Vec<HttpRequest>,
+}
+
+impl HttpRequest {
+    /// Value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+        Box::pin(async move {
+            let mut builder = self.client.get(req.url);
+            for (name, value) in &req.headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+            let resp = builder.body("").send().await?;
+            let status = resp.status();
+            let body = resp.text().await?;
+            Ok(HttpResponse { status, body })
+        })
+    }
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers requests to paths ending with `path` with `body`.
+    pub fn respond(&self, path: &str, body: &str) -> &Self {
+        self.respond_with_status(path, StatusCode::OK, body)
+    }
+
+    /// Answers requests to paths ending with `path` with `status` and `body`. Responses added
+    /// later take precedence.
+    pub fn respond_with_status(&self, path: &str, status: StatusCode, body: &str) -> &Self {
+        let resp = HttpResponse {
+            status,
+            body: body.to_string(),
+        };
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push((path.to_string(), resp));
+        self
+    }
+
+    /// Every request sent so far, in order.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+        let mut state = self.state.lock().unwrap();
+        let resp = state
+            .responses
+            .iter()
+            .rev()
+            .find(|(path, _)| req.url.path().ends_with(path.as_str()))
+            .map(|(_, resp)| resp.clone())
+            .unwrap_or_else(|| HttpResponse {
+                status: StatusCode::NOT_FOUND,
+                body: format!("no response for {}", req.url.path()),
+            });
+        state.requests.push(req);
+        Box::pin(async move { Ok(resp) })
+    }
+}
@@ -1,121 +1,71 @@
 #![allow(clippy::unreadable_literal)]
 #![allow(clippy::excessive_precision)]
 
-use std::convert::Infallible;
-
 use {
-    daummap,
-    hyper::{
-        header::HeaderValue,
-        service::{make_service_fn, service_fn},
-        Body, Response, Server,
-    },
+    daummap::testing::{MockKakao, ReceivedRequest},
+    reqwest::StatusCode,
+    std::time::Duration,
 };
 
-#[tokio::test]
-async fn test_address() {
-    static RESP: &'static str = r#"{
+static EMPTY_PLACES: &str = r#"{
   "meta": {
-    "total_count": 4,
-    "pageable_count": 4,
+    "same_name": null,
+    "pageable_count": 0,
+    "total_count": 0,
     "is_end": true
   },
-  "documents": [
-    {
-      "address_name": "전북 익산시 부송동 100",
-      "y": "35.97664845766847",
-      "x": "126.99597295767953",
-      "address_type": "REGION_ADDR",
-      "address": {
-        "address_name": "전북 익산시 부송동 100",
-        "region_1depth_name": "전북",
-        "region_2depth_name": "익산시",
-        "region_3depth_name": "부송동",
-        "region_3depth_h_name": "삼성동",
-        "h_code": "4514069000",
-        "b_code": "4514013400",
-        "mountain_yn": "N",
-        "main_address_no": "100",
-        "sub_address_no": "",
-        "zip_code": "570972",
-        "x": "126.99597295767953",
-        "y": "35.97664845766847"
-      },
-      "road_address": {
-        "address_name": "전북 익산시 망산길 11-17",
-        "region_1depth_name": "전북",
-        "region_2depth_name": "익산시",
-        "region_3depth_name": "부송동",
-        "road_name": "망산길",
-        "underground_yn": "N",
-        "main_building_no": "11",
-        "sub_building_no": "17",
-        "building_name": "",
-        "zone_no": "54547",
-        "y": "35.976749396987046",
-        "x": "126.99599512792346"
-      }
-    }
-  ]
+  "documents": []
 }"#;
 
-    let (called_sender, called_receiver) = std::sync::mpsc::channel();
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-
-    let service = make_service_fn(move |_| {
-        let called_sender = called_sender.clone();
-        async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
-                let called_sender = called_sender.clone();
-                async move {
-                    let uri = req.uri();
-                    assert_eq!(uri.path(), "/search/address.json");
-                    assert_eq!(uri.query(), Some("query=address&page=2&size=5"));
-
-                    let headers = req.headers();
-                    assert_eq!(
-                        headers.get("Authorization"),
-                        Some(&HeaderValue::from_static("KakaoAK key"))
-                    );
-
-                    called_sender.send(()).unwrap();
-
-                    Ok::<_, Infallible>(Response::<Body>::new(RESP.into()))
-                }
-            }))
-        }
-    });
-
-    let server = Server::bind(&"127.0.0.1:12121".parse().unwrap())
-        .serve(service)
-        .with_graceful_shutdown(async { shutdown_receiver.await.unwrap() });
+/// A pharmacy document with the name, URL and id made from `id`.
+fn place(id: impl std::fmt::Display) -> String {
+    format!(
+        r#"{{
+      "place_name": "place {id}",
+      "distance": "",
+      "place_url": "http://place.map.daum.net/{id}",
+      "category_name": "의료,건강 > 약국",
+      "address_name": "서울 강남구 대치동 943-16",
+      "road_address_name": "서울 강남구 테헤란로84길 17",
+      "id": "{id}",
+      "phone": "02-558-5476",
+      "category_group_code": "PM9",
+      "category_group_name": "약국",
+      "x": "127.05897078335246",
+      "y": "37.506051888130386"
+    }}"#,
+        id = id
+    )
+}
 
-    tokio::spawn(async {
-        if let Err(e) = server.await {
-            panic!("{}", e);
-        }
-    });
+#[tokio::test]
+async fn test_address() {
+    let mock = MockKakao::start().await;
 
     let resp = daummap::AddressRequest::new("key", "address")
-        .base_url("http://localhost:12121")
+        .base_url(&mock.url())
         .page(2)
         .size(5)
         .get()
         .await
         .unwrap();
 
-    shutdown_sender.send(()).unwrap();
-    called_receiver.try_recv().unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/search/address.json");
+    assert_eq!(requests[0].query, "query=address&page=2&size=5");
+    assert_eq!(requests[0].app_key.as_deref(), Some("key"));
 
     assert_eq!(resp.total_count, 4);
     assert_eq!(resp.pageable_count, 4);
-    assert_eq!(resp.is_end, true);
+    assert!(resp.is_end);
 
     assert_eq!(resp.addresses.len(), 1);
 
     let address = &resp.addresses[0];
 
     assert_eq!(address.address, Some("전북 익산시 부송동 100".to_string()));
+    assert_eq!(address.address_type, Some(daummap::AddressType::RegionAddr));
 
     assert!(address.land_lot.is_some());
     let land_lot = address.land_lot.as_ref().unwrap();
@@ -124,174 +74,60 @@ async fn test_address() {
     assert!(address.road.is_some());
     let road = address.road.as_ref().unwrap();
     assert_eq!(&road.address, "전북 익산시 망산길 11-17");
+
+    assert_eq!(address.longitude, Some(126.99597295767953));
+    assert_eq!(address.latitude, Some(35.97664845766847));
+    assert_eq!(
+        address.best_coordinate(),
+        Some((126.99599512792346, 35.976749396987046))
+    );
 }
 
 #[tokio::test]
 async fn test_coord2region() {
-    static RESP: &'static str = r#"{
-  "meta": {
-    "total_count": 2
-  },
-  "documents": [
-    {
-      "region_type": "B",
-      "address_name": "경기도 성남시 분당구 삼평동",
-      "region_1depth_name": "경기도",
-      "region_2depth_name": "성남시 분당구",
-      "region_3depth_name": "삼평동",
-      "region_4depth_name": "",
-      "code": "4113510900",
-      "x": 127.10459896729914,
-      "y": 37.40269721785548
-    },
-    {
-      "region_type": "H",
-      "address_name": "경기도 성남시 분당구 삼평동",
-      "region_1depth_name": "경기도",
-      "region_2depth_name": "성남시 분당구",
-      "region_3depth_name": "삼평동",
-      "region_4depth_name": "",
-      "code": "4113565500",
-      "x": 127.1163593869371,
-      "y": 37.40612091848614
-    }
-  ]
-}"#;
-
-    let (called_sender, called_receiver) = std::sync::mpsc::channel();
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-
-    let service = make_service_fn(move |_| {
-        let called_sender = called_sender.clone();
-        async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
-                let called_sender = called_sender.clone();
-                async move {
-                    let uri = req.uri();
-                    assert_eq!(uri.path(), "/geo/coord2regioncode.json");
-                    assert_eq!(uri.query(), Some("page=2&x=123.123&y=456.456"));
-
-                    let headers = req.headers();
-                    assert_eq!(
-                        headers.get("Authorization"),
-                        Some(&HeaderValue::from_static("KakaoAK key"))
-                    );
-
-                    called_sender.send(()).unwrap();
-
-                    Ok::<_, Infallible>(Response::<Body>::new(RESP.into()))
-                }
-            }))
-        }
-    });
-
-    let server = Server::bind(&"127.0.0.1:12122".parse().unwrap())
-        .serve(service)
-        .with_graceful_shutdown(async { shutdown_receiver.await.unwrap() });
-
-    tokio::spawn(async {
-        if let Err(e) = server.await {
-            panic!("{}", e);
-        }
-    });
+    let mock = MockKakao::start().await;
 
     let resp = daummap::CoordRequest::new("key", 123.123, 456.456)
-        .base_url("http://localhost:12122")
+        .base_url(&mock.url())
         .page(2)
         .get_region()
         .await
         .unwrap();
 
-    shutdown_sender.send(()).unwrap();
-    called_receiver.try_recv().unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/geo/coord2regioncode.json");
+    assert_eq!(requests[0].query, "page=2&x=123.123&y=456.456");
+    assert_eq!(requests[0].app_key.as_deref(), Some("key"));
 
     assert_eq!(resp.len(), 2);
     assert_eq!(&resp[0].address, "경기도 성남시 분당구 삼평동");
     assert_eq!(resp[0].code, Some(4113510900));
     assert_eq!(&resp[1].address, "경기도 성남시 분당구 삼평동");
     assert_eq!(resp[1].code, Some(4113565500));
+    assert_eq!(resp[0].region_type, Some(daummap::RegionType::Legal));
+    assert_eq!(
+        resp[1].region_type,
+        Some(daummap::RegionType::Administrative)
+    );
 }
 
 #[tokio::test]
 async fn test_coord2address() {
-    static RESP: &'static str = r#"{
-  "meta": {
-    "total_count": 1
-  },
-  "documents": [
-    {
-      "road_address": {
-        "address_name": "경기도 안성시 죽산면 죽산초교길 69-4",
-        "region_1depth_name": "경기",
-        "region_2depth_name": "안성시",
-        "region_3depth_name": "죽산면",
-        "road_name": "죽산초교길",
-        "underground_yn": "N",
-        "main_building_no": "69",
-        "sub_building_no": "4",
-        "building_name": "무지개아파트",
-        "zone_no": "17519"
-      },
-      "address": {
-        "address_name": "경기 안성시 죽산면 죽산리 343-1",
-        "region_1depth_name": "경기",
-        "region_2depth_name": "안성시",
-        "region_3depth_name": "죽산면 죽산리",
-        "mountain_yn": "N",
-        "main_address_no": "343",
-        "sub_address_no": "1",
-        "zip_code": "456894"
-      }
-    }
-  ]
-}"#;
-
-    let (called_sender, called_receiver) = std::sync::mpsc::channel();
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-
-    let service = make_service_fn(move |_| {
-        let called_sender = called_sender.clone();
-        async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
-                let called_sender = called_sender.clone();
-                async move {
-                    let uri = req.uri();
-                    assert_eq!(uri.path(), "/geo/coord2address.json");
-                    assert_eq!(uri.query(), Some("page=2&x=123.123&y=456.456"));
-
-                    let headers = req.headers();
-                    assert_eq!(
-                        headers.get("Authorization"),
-                        Some(&HeaderValue::from_static("KakaoAK key"))
-                    );
-
-                    called_sender.send(()).unwrap();
-
-                    Ok::<_, Infallible>(Response::<Body>::new(RESP.into()))
-                }
-            }))
-        }
-    });
-
-    let server = Server::bind(&"127.0.0.1:12123".parse().unwrap())
-        .serve(service)
-        .with_graceful_shutdown(async { shutdown_receiver.await.unwrap() });
-
-    tokio::spawn(async {
-        if let Err(e) = server.await {
-            panic!("{}", e);
-        }
-    });
+    let mock = MockKakao::start().await;
 
     let resp = daummap::CoordRequest::new("key", 123.123, 456.456)
-        .base_url("http://localhost:12123")
+        .base_url(&mock.url())
         .page(2)
         .get_address()
         .await
         .unwrap();
 
-    shutdown_sender.send(()).unwrap();
-    called_receiver.try_recv().unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/geo/coord2address.json");
+    assert_eq!(requests[0].query, "page=2&x=123.123&y=456.456");
+    assert_eq!(requests[0].app_key.as_deref(), Some("key"));
 
     assert_eq!(resp.len(), 1);
 
@@ -308,77 +144,10 @@ async fn test_coord2address() {
 
 #[tokio::test]
 async fn test_keyword() {
-    static RESP: &'static str = r#"{
-  "meta": {
-    "same_name": {
-      "region": [],
-      "keyword": "카카오프렌즈",
-      "selected_region": ""
-    },
-    "pageable_count": 14,
-    "total_count": 14,
-    "is_end": true
-  },
-  "documents": [
-    {
-      "place_name": "카카오프렌즈 코엑스점",
-      "distance": "418",
-      "place_url": "http://place.map.daum.net/26338954",
-      "category_name": "가정,생활 > 문구,사무용품 > 디자인문구 > 카카오프렌즈",
-      "address_name": "서울 강남구 삼성동 159",
-      "road_address_name": "서울 강남구 영동대로 513",
-      "id": "26338954",
-      "phone": "02-6002-1880",
-      "category_group_code": "",
-      "category_group_name": "",
-      "x": "127.05902969025047",
-      "y": "37.51207412593136"
-    }
-  ]
-}"#;
-
-    let (called_sender, called_receiver) = std::sync::mpsc::channel();
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-
-    let service = make_service_fn(move |_| {
-        let called_sender = called_sender.clone();
-        async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
-                let called_sender = called_sender.clone();
-                async move {
-                    let uri = req.uri();
-                    assert_eq!(uri.path(), "/search/keyword.json");
-                    assert_eq!(
-                uri.query(),
-                Some("query=keyword&page=2&size=5&sort=accuracy&x=123.123&y=456.456&radius=1234")
-            );
-
-                    let headers = req.headers();
-                    assert_eq!(
-                        headers.get("Authorization"),
-                        Some(&HeaderValue::from_static("KakaoAK key"))
-                    );
-
-                    called_sender.send(()).unwrap();
-
-                    Ok::<_, Infallible>(Response::<Body>::new(RESP.into()))
-                }
-            }))
-        }
-    });
-
-    let server = Server::bind(&"127.0.0.1:12124".parse().unwrap())
-        .serve(service)
-        .with_graceful_shutdown(async { shutdown_receiver.await.unwrap() });
-
-    tokio::spawn(async {
-        if let Err(e) = server.await {
-            panic!("{}", e);
-        }
-    });
+    let mock = MockKakao::start().await;
 
     let resp = daummap::KeywordRequest::new("key", "keyword")
-        .base_url("http://localhost:12124")
+        .base_url(&mock.url())
         .coord(123.123, 456.456)
         .radius(1234)
         .page(2)
@@ -387,12 +156,18 @@ async fn test_keyword() {
         .await
         .unwrap();
 
-    shutdown_sender.send(()).unwrap();
-    called_receiver.try_recv().unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/search/keyword.json");
+    assert_eq!(
+        requests[0].query,
+        "query=keyword&page=2&size=5&sort=accuracy&x=123.123&y=456.456&radius=1234"
+    );
+    assert_eq!(requests[0].app_key.as_deref(), Some("key"));
 
     assert_eq!(resp.total_count, 14);
     assert_eq!(resp.pageable_count, 14);
-    assert_eq!(resp.is_end, true);
+    assert!(resp.is_end);
 
     assert_eq!(resp.places.len(), 1);
 
@@ -402,70 +177,7 @@ async fn test_keyword() {
 
 #[tokio::test]
 async fn test_category() {
-    static RESP: &'static str = r#"{
-  "meta": {
-    "same_name": null,
-    "pageable_count": 11,
-    "total_count": 11,
-    "is_end": true
-  },
-  "documents": [
-    {
-      "place_name": "장생당약국",
-      "distance": "",
-      "place_url": "http://place.map.daum.net/16618597",
-      "category_name": "의료,건강 > 약국",
-      "address_name": "서울 강남구 대치동 943-16",
-      "road_address_name": "서울 강남구 테헤란로84길 17",
-      "id": "16618597",
-      "phone": "02-558-5476",
-      "category_group_code": "PM9",
-      "category_group_name": "약국",
-      "x": "127.05897078335246",
-      "y": "37.506051888130386"
-    }
-  ]
-}"#;
-
-    let (called_sender, called_receiver) = std::sync::mpsc::channel();
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-
-    let service = make_service_fn(move |_| {
-        let called_sender = called_sender.clone();
-        async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
-                let called_sender = called_sender.clone();
-                async move {
-                    let uri = req.uri();
-                    assert_eq!(uri.path(), "/search/category.json");
-                    assert_eq!(
-                uri.query(),
-                Some("category_group_code=PM9&page=2&size=5&sort=accuracy&rect=123.123%2C456.456%2C321.321%2C654.654")
-            );
-
-                    let headers = req.headers();
-                    assert_eq!(
-                        headers.get("Authorization"),
-                        Some(&HeaderValue::from_static("KakaoAK key"))
-                    );
-
-                    called_sender.send(()).unwrap();
-
-                    Ok::<_, Infallible>(Response::<Body>::new(RESP.into()))
-                }
-            }))
-        }
-    });
-
-    let server = Server::bind(&"127.0.0.1:12125".parse().unwrap())
-        .serve(service)
-        .with_graceful_shutdown(async { shutdown_receiver.await.unwrap() });
-
-    tokio::spawn(async {
-        if let Err(e) = server.await {
-            panic!("{}", e);
-        }
-    });
+    let mock = MockKakao::start().await;
 
     let resp = daummap::CategoryRequest::rect(
         "key",
@@ -475,22 +187,1662 @@ async fn test_category() {
         321.321,
         654.654,
     )
-    .base_url("http://localhost:12125")
+    .base_url(&mock.url())
     .page(2)
     .size(5)
     .get()
     .await
     .unwrap();
 
-    shutdown_sender.send(()).unwrap();
-    called_receiver.try_recv().unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/search/category.json");
+    assert_eq!(
+        requests[0].query,
+        "category_group_code=PM9&page=2&size=5&sort=accuracy&rect=123.123%2C456.456%2C321.321%2C654.654"
+    );
+    assert_eq!(requests[0].app_key.as_deref(), Some("key"));
 
     assert_eq!(resp.total_count, 11);
     assert_eq!(resp.pageable_count, 11);
-    assert_eq!(resp.is_end, true);
+    assert!(resp.is_end);
 
     assert_eq!(resp.places.len(), 1);
 
     let place = &resp.places[0];
     assert_eq!(&place.name, "장생당약국");
 }
+
+#[tokio::test]
+async fn test_client() {
+    let mock = MockKakao::start().await;
+    mock.on("/search/keyword.json").respond(EMPTY_PLACES);
+
+    let client = mock
+        .builder("key")
+        .size(7)
+        .sort(daummap::Sort::Distance)
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let resp = client.keyword("keyword").get().await.unwrap();
+        assert_eq!(resp.total_count, 0);
+        assert!(resp.places.is_empty());
+    }
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    for req in requests {
+        assert_eq!(req.path, "/search/keyword.json");
+        assert_eq!(req.query, "query=keyword&page=1&size=7&sort=distance");
+        assert_eq!(req.app_key.as_deref(), Some("key"));
+    }
+}
+
+#[tokio::test]
+async fn test_client_projected() {
+    use daummap::proj::Projection;
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/category.json").respond(EMPTY_PLACES);
+    let client = mock.client("key");
+
+    let (x, y) = Projection::Korea2000Central.from_wgs84(127.1086228, 37.4012191);
+    client
+        .coord_projected(Projection::Korea2000Central, x, y)
+        .get_region()
+        .await
+        .unwrap();
+    let (x1, y1) = Projection::Wtm.from_wgs84(127.0561466, 37.5058277);
+    let (x2, y2) = Projection::Wtm.from_wgs84(127.0602340, 37.5142554);
+    client
+        .category_rect_projected(
+            daummap::CategoryGroup::Pharmacy,
+            Projection::Wtm,
+            x1,
+            y1,
+            x2,
+            y2,
+        )
+        .get()
+        .await
+        .unwrap();
+
+    let requests = mock.requests();
+    let param = |i: usize, name: &str| requests[i].param(name).unwrap().parse::<f64>().unwrap();
+    assert!((param(0, "x") - 127.1086228).abs() < 1e-9);
+    assert!((param(0, "y") - 37.4012191).abs() < 1e-9);
+
+    let rect = requests[1]
+        .param("rect")
+        .unwrap()
+        .split(',')
+        .map(|v| v.parse::<f64>().unwrap())
+        .collect::<Vec<_>>();
+    // The enclosing box is slightly wider than the original corners.
+    assert!(rect[0] <= 127.0561466 + 1e-9 && rect[0] > 127.0561466 - 1e-4);
+    assert!(rect[1] <= 37.5058277 + 1e-9 && rect[1] > 37.5058277 - 1e-4);
+    assert!(rect[2] >= 127.0602340 - 1e-9 && rect[2] < 127.0602340 + 1e-4);
+    assert!(rect[3] >= 37.5142554 - 1e-9 && rect[3] < 37.5142554 + 1e-4);
+}
+
+#[tokio::test]
+async fn test_retry() {
+    let mock = MockKakao::start().await;
+    mock.on("/search/keyword.json")
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .respond("Service Unavailable");
+    mock.on("/search/keyword.json")
+        .param("query", "flaky")
+        .respond(EMPTY_PLACES);
+    mock.on("/search/keyword.json")
+        .param("query", "flaky")
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .times(2)
+        .respond("Service Unavailable");
+
+    let client = mock
+        .builder("key")
+        .retry(
+            daummap::RetryPolicy::new()
+                .max_attempts(3)
+                .base_delay(Duration::from_millis(1))
+                .clone(),
+        )
+        .build()
+        .unwrap();
+
+    let resp = client.keyword("flaky").get().await.unwrap();
+    assert_eq!(resp.total_count, 0);
+    assert_eq!(mock.take_requests().len(), 3);
+
+    let err = client.keyword("down").get().await.unwrap_err();
+    assert_eq!(err.attempts(), Some(3));
+    assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(mock.take_requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_rate_limiter() {
+    let mock = MockKakao::start().await;
+    mock.on("/search/keyword.json").respond(EMPTY_PLACES);
+
+    let limiter = daummap::RateLimiter::new()
+        .per_second(1)
+        .daily_limit(3)
+        .fail_fast(true)
+        .clone();
+    let client = mock
+        .builder("key")
+        .rate_limiter(limiter.clone())
+        .build()
+        .unwrap();
+
+    client.keyword("keyword").get().await.unwrap();
+    match client.keyword("keyword").get().await {
+        Err(daummap::Error::RateLimited { retry_after, daily }) => {
+            assert!(!daily);
+            assert!(retry_after <= Duration::from_secs(1));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(limiter.usage().count, 1);
+
+    let waiting = mock
+        .builder("key")
+        .rate_limiter(
+            limiter
+                .clone()
+                .per_second(20)
+                .burst(1)
+                .fail_fast(false)
+                .clone(),
+        )
+        .build()
+        .unwrap();
+    let started = std::time::Instant::now();
+    waiting.keyword("keyword").get().await.unwrap();
+    waiting.keyword("keyword").get().await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(40));
+    assert_eq!(limiter.usage().count, 3);
+
+    match waiting.keyword("keyword").get().await {
+        Err(daummap::Error::RateLimited { daily, .. }) => assert!(daily),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_layers() {
+    use {
+        daummap::{
+            layer::{Layer, LoggingLayer, MetricsLayer, OutgoingRequest, RedactKey},
+            transport::HttpResponse,
+        },
+        std::sync::{Arc, Mutex},
+    };
+
+    #[derive(Debug)]
+    struct Egress {
+        base_url: String,
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Layer for Egress {
+        fn on_request(&self, req: &mut OutgoingRequest) {
+            req.base_url = self.base_url.clone();
+            req.headers
+                .push(("X-Request-Id".to_string(), "req-1".to_string()));
+            self.events
+                .lock()
+                .unwrap()
+                .push("egress request".to_string());
+        }
+
+        fn on_response(
+            &self,
+            _: &OutgoingRequest,
+            _: Result<&HttpResponse, &daummap::Error>,
+            _: Duration,
+        ) {
+            self.events
+                .lock()
+                .unwrap()
+                .push("egress response".to_string());
+        }
+    }
+
+    #[derive(Debug)]
+    struct Spy(Arc<Mutex<Vec<String>>>);
+
+    impl Layer for Spy {
+        fn on_request(&self, req: &mut OutgoingRequest) {
+            let auth = req.header("Authorization").unwrap().to_string();
+            self.0.lock().unwrap().push(format!("spy request {}", auth));
+            req.base_url = "http://unreachable.invalid".to_string();
+        }
+
+        fn on_response(
+            &self,
+            _: &OutgoingRequest,
+            _: Result<&HttpResponse, &daummap::Error>,
+            _: Duration,
+        ) {
+            self.0.lock().unwrap().push("spy response".to_string());
+        }
+    }
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/keyword.json")
+        .param("query", "down")
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .respond("Service Unavailable");
+    mock.expect_app_key("secret");
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let metrics = MetricsLayer::new();
+    let client = daummap::KakaoClient::builder("secret")
+        .base_url("http://proxy.invalid/v2/local")
+        .layer(Egress {
+            base_url: mock.url(),
+            events: events.clone(),
+        })
+        .layer(RedactKey::new(Spy(events.clone())))
+        .layer(LoggingLayer::with_writer({
+            let lines = lines.clone();
+            move |line| lines.lock().unwrap().push(line.to_string())
+        }))
+        .layer(metrics.clone())
+        .build()
+        .unwrap();
+
+    let resp = client.keyword("카카오프렌즈").get().await.unwrap();
+    assert_eq!(resp.total_count, 14);
+    client.keyword("down").get().await.unwrap_err();
+    client.address("전북 삼성동 100").get().await.unwrap();
+
+    assert_eq!(mock.requests().len(), 3);
+    assert_eq!(
+        events.lock().unwrap()[..4],
+        [
+            "egress request",
+            "spy request KakaoAK ***",
+            "spy response",
+            "egress response",
+        ]
+    );
+
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with(&format!("GET {}/search/keyword.json?query=", mock.url())));
+    assert!(lines[0].contains("[Authorization: KakaoAK ***, X-Request-Id: req-1] -> 200 OK"));
+    assert!(lines[1].contains("-> 503 Service Unavailable"));
+    assert!(lines.iter().all(|line| !line.contains("secret")));
+
+    // A short key is redacted where it is sent, not wherever it appears.
+    let req = OutgoingRequest {
+        base_url: "https://dapi.kakao.com/v2/local".to_string(),
+        path: "search/keyword.json".to_string(),
+        params: vec![
+            ("query".to_string(), "kakao".to_string()),
+            ("key".to_string(), "ka".to_string()),
+        ],
+        headers: vec![("Authorization".to_string(), "KakaoAK ka".to_string())],
+    };
+    let redacted = req.redacted();
+    assert_eq!(redacted.base_url, req.base_url);
+    assert_eq!(redacted.path, req.path);
+    assert_eq!(
+        redacted.params,
+        [
+            ("query".to_string(), "kakao".to_string()),
+            ("key".to_string(), "***".to_string()),
+        ]
+    );
+    assert_eq!(redacted.app_key(), Some("***"));
+
+    let keyword = metrics.get("/search/keyword.json");
+    assert_eq!((keyword.requests, keyword.errors), (2, 1));
+    let snapshot = metrics.snapshot();
+    assert_eq!(
+        snapshot.keys().collect::<Vec<_>>(),
+        ["search/address.json", "search/keyword.json"]
+    );
+    assert_eq!(snapshot["search/address.json"].errors, 0);
+    metrics.reset();
+    assert!(metrics.snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn test_stream() {
+    use futures::TryStreamExt;
+
+    fn address(id: usize) -> String {
+        format!(
+            r#"{{
+      "address_name": "address {id}",
+      "y": "35.97664845766847",
+      "x": "126.99597295767953",
+      "address_type": "REGION",
+      "address": null,
+      "road_address": null
+    }}"#,
+            id = id
+        )
+    }
+
+    fn page(req: &ReceivedRequest) -> String {
+        let page = req.param("page").unwrap().parse::<usize>().unwrap();
+        let documents = if req.path == "/search/address.json" {
+            [address(page * 2), address(page * 2 + 1)]
+        } else {
+            [place(page * 2), place(page * 2 + 1)]
+        };
+        format!(
+            r#"{{
+  "meta": {{ "total_count": 6, "pageable_count": 6, "is_end": {} }},
+  "documents": [{}]
+}}"#,
+            page == 3,
+            documents.join(",")
+        )
+    }
+
+    // Kakao lowering pageable_count after the first page.
+    fn shrinking_page(req: &ReceivedRequest) -> String {
+        let page = req.param("page").unwrap().parse::<usize>().unwrap();
+        format!(
+            r#"{{
+  "meta": {{ "total_count": 10, "pageable_count": {}, "is_end": false }},
+  "documents": [{}]
+}}"#,
+            if page == 1 { 10 } else { 4 },
+            [place(page * 2), place(page * 2 + 1)].join(",")
+        )
+    }
+
+    let mock = MockKakao::start().await;
+    for path in &[
+        "/search/keyword.json",
+        "/search/category.json",
+        "/search/address.json",
+    ] {
+        mock.on(path).respond_with(page);
+    }
+    mock.on("/search/keyword.json")
+        .param("query", "shrinking")
+        .respond_with(shrinking_page);
+
+    let client = mock.builder("key").size(2).build().unwrap();
+
+    let places = client
+        .keyword("keyword")
+        .prefetch(1)
+        .stream()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let ids = places.iter().map(|p| p.id.unwrap()).collect::<Vec<_>>();
+    assert_eq!(ids, vec![2, 3, 4, 5, 6, 7]);
+    assert_eq!(mock.take_requests().len(), 3);
+
+    let places = client
+        .category_rect(daummap::CategoryGroup::Pharmacy, 1.0, 2.0, 3.0, 4.0)
+        .page(2)
+        .stream()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(places.len(), 4);
+    assert_eq!(mock.take_requests().len(), 2);
+
+    let addresses = client
+        .address("address")
+        .stream()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(addresses.len(), 6);
+    assert_eq!(addresses[0].address, Some("address 2".to_string()));
+    assert_eq!(
+        addresses[0].address_type,
+        Some(daummap::AddressType::Region)
+    );
+    assert_eq!(
+        addresses[0].best_coordinate(),
+        Some((126.99597295767953, 35.97664845766847))
+    );
+    assert_eq!(mock.take_requests().len(), 3);
+
+    // Pages prefetched past the end set by the second page are dropped.
+    let places = client
+        .keyword("shrinking")
+        .prefetch(3)
+        .stream()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let ids = places.iter().map(|p| p.id.unwrap()).collect::<Vec<_>>();
+    assert_eq!(ids, vec![2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_sweep() {
+    fn tile(req: &ReceivedRequest) -> String {
+        let rect = req
+            .param("rect")
+            .unwrap()
+            .split(',')
+            .map(|v| v.parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+
+        // The whole box holds too many places, every quadrant holds two: one of its own and one
+        // lying on the shared center.
+        if rect[2] - rect[0] > 1.5 {
+            format!(
+                r#"{{ "meta": {{ "total_count": 1000, "pageable_count": 675, "is_end": false }}, "documents": [{}] }}"#,
+                place("0")
+            )
+        } else {
+            format!(
+                r#"{{ "meta": {{ "total_count": 2, "pageable_count": 2, "is_end": true }}, "documents": [{}, {}] }}"#,
+                place(format!("{}{}", rect[0] as i32, rect[1] as i32)),
+                place("1000")
+            )
+        }
+    }
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/category.json").respond_with(tile);
+    mock.on("/search/keyword.json").respond_with(tile);
+
+    let client = mock.client("key");
+
+    let result = daummap::sweep::Sweep::category(
+        &client,
+        daummap::CategoryGroup::Pharmacy,
+        0.0,
+        0.0,
+        2.0,
+        2.0,
+    )
+    .run()
+    .await
+    .unwrap();
+
+    let mut ids = result
+        .places
+        .iter()
+        .map(|p| p.id.unwrap())
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![0, 1, 10, 11, 1000]);
+
+    assert_eq!(result.stats.tiles, 5);
+    assert_eq!(result.stats.splits, 1);
+    assert_eq!(result.stats.depth, 1);
+    assert_eq!(result.stats.requests, 5);
+    assert_eq!(result.stats.truncated_tiles, 0);
+    assert_eq!(result.stats.duplicates, 3);
+    assert!((result.stats.coverage - 1.0).abs() < f64::EPSILON);
+
+    let result = daummap::sweep::Sweep::keyword(&client, "약국", 0.0, 0.0, 2.0, 2.0)
+        .max_depth(0)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(result.stats.truncated_tiles, 1);
+    assert_eq!(result.stats.coverage, 0.0);
+}
+
+#[tokio::test]
+async fn test_transcoord() {
+    let mock = MockKakao::start().await;
+
+    let resp = daummap::TransCoordRequest::new("key", 209640.5, 433632.5)
+        .base_url(&mock.url())
+        .input_coord(daummap::CoordSystem::Wtm)
+        .get()
+        .await
+        .unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/geo/transcoord.json");
+    assert_eq!(
+        requests[0].query,
+        "x=209640.5&y=433632.5&input_coord=WTM&output_coord=WGS84"
+    );
+    assert_eq!(requests[0].app_key.as_deref(), Some("key"));
+
+    assert_eq!(resp.len(), 1);
+    assert_eq!(resp[0].x, 127.1086228);
+    assert_eq!(resp[0].y, 37.4012191);
+    assert_eq!(resp[0].coord_system, daummap::CoordSystem::Wgs84);
+}
+
+#[tokio::test]
+async fn test_coord2region_coord_system() {
+    static RESP: &str = r#"{
+  "meta": {
+    "total_count": 1
+  },
+  "documents": [
+    {
+      "region_type": "B",
+      "address_name": "경기도 성남시 분당구 삼평동",
+      "region_1depth_name": "경기도",
+      "region_2depth_name": "성남시 분당구",
+      "region_3depth_name": "삼평동",
+      "region_4depth_name": "",
+      "code": "4113510900",
+      "x": 523953.0,
+      "y": 1084098.5
+    }
+  ]
+}"#;
+
+    let mock = MockKakao::start().await;
+    mock.on("/geo/coord2regioncode.json").respond(RESP);
+
+    let resp = daummap::CoordRequest::new("key", 209640.5, 433632.5)
+        .base_url(&mock.url())
+        .input_coord(daummap::CoordSystem::Wtm)
+        .output_coord(daummap::CoordSystem::WCongnamul)
+        .get_region()
+        .await
+        .unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].query,
+        "page=1&x=209640.5&y=433632.5&input_coord=WTM&output_coord=WCONGNAMUL"
+    );
+
+    assert_eq!(resp.len(), 1);
+    assert_eq!(resp[0].longitude, Some(523953.0));
+    assert_eq!(resp[0].latitude, Some(1084098.5));
+    assert_eq!(resp[0].coord_system, daummap::CoordSystem::WCongnamul);
+}
+
+#[tokio::test]
+async fn test_address_analyze_type() {
+    static RESP: &str = r#"{
+  "meta": {
+    "total_count": 1,
+    "pageable_count": 1,
+    "is_end": true
+  },
+  "documents": [
+    {
+      "address_name": "전북 익산시 망산길 11-17",
+      "y": "35.976749396987046",
+      "x": "126.99599512792346",
+      "address_type": "ROAD_ADDR",
+      "address": null,
+      "road_address": {
+        "address_name": "전북 익산시 망산길 11-17",
+        "region_1depth_name": "전북",
+        "region_2depth_name": "익산시",
+        "region_3depth_name": "부송동",
+        "road_name": "망산길",
+        "underground_yn": "N",
+        "main_building_no": "11",
+        "sub_building_no": "17",
+        "building_name": "",
+        "zone_no": "54547",
+        "y": "35.976749396987046",
+        "x": "126.99599512792346"
+      }
+    }
+  ]
+}"#;
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/address.json").respond(RESP);
+
+    let resp = daummap::AddressRequest::new("key", "address")
+        .base_url(&mock.url())
+        .analyze_type(daummap::AnalyzeType::Exact)
+        .get()
+        .await
+        .unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].query,
+        "query=address&page=1&size=15&analyze_type=exact"
+    );
+
+    assert_eq!(resp.addresses.len(), 1);
+    assert_eq!(
+        resp.addresses[0].address_type,
+        Some(daummap::AddressType::RoadAddr)
+    );
+    assert!(resp.addresses[0].land_lot.is_none());
+}
+
+#[tokio::test]
+async fn test_coord_round_trip() {
+    let mock = MockKakao::start().await;
+    let client = mock.client("key");
+
+    let resp = client.keyword("keyword").get().await.unwrap();
+    let place = &resp.places[0];
+    let (longitude, latitude) = (place.longitude.unwrap(), place.latitude.unwrap());
+    assert_eq!(longitude.to_string(), "127.05902969025047");
+    assert_eq!(latitude.to_string(), "37.51207412593136");
+
+    client
+        .coord(longitude, latitude)
+        .get_region()
+        .await
+        .unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests[1].path, "/geo/coord2regioncode.json");
+    assert_eq!(
+        requests[1].query,
+        "page=1&x=127.05902969025047&y=37.51207412593136"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let place = daummap::Place {
+        id: Some(26338954),
+        name: "카카오프렌즈 코엑스점".to_string(),
+        category: "가정,생활 > 문구,사무용품".to_string(),
+        category_group: Some(daummap::CategoryGroup::Food),
+        phone: "02-6002-1880".to_string(),
+        address: "서울 강남구 삼성동 159".to_string(),
+        road_address: "서울 강남구 영동대로 513".to_string(),
+        longitude: Some(127.05902969025047),
+        latitude: Some(37.51207412593136),
+        url: "http://place.map.daum.net/26338954".to_string(),
+        distance: None,
+    };
+
+    let json = serde_json::to_value(&place).unwrap();
+    assert_eq!(json["category_group"], "FD6");
+    assert_eq!(json["longitude"], 127.05902969025047);
+
+    let place: daummap::Place = serde_json::from_value(json).unwrap();
+    assert!(matches!(
+        place.category_group,
+        Some(daummap::CategoryGroup::Food)
+    ));
+    assert_eq!(place.longitude, Some(127.05902969025047));
+    assert_eq!(place.latitude, Some(37.51207412593136));
+
+    assert_eq!(
+        serde_json::to_string(&daummap::AddressType::RegionAddr).unwrap(),
+        r#""REGION_ADDR""#
+    );
+    assert_eq!(
+        serde_json::to_string(&daummap::CoordSystem::WCongnamul).unwrap(),
+        r#""WCONGNAMUL""#
+    );
+    assert!(serde_json::from_str::<daummap::CategoryGroup>(r#""XX0""#).is_err());
+}
+
+#[cfg(feature = "geo")]
+#[test]
+fn test_geo() {
+    use std::convert::TryFrom;
+
+    let place = daummap::Place {
+        id: Some(26338954),
+        name: "카카오프렌즈 코엑스점".to_string(),
+        category: "가정,생활 > 문구,사무용품".to_string(),
+        category_group: Some(daummap::CategoryGroup::Cafe),
+        phone: "02-6002-1880".to_string(),
+        address: "서울 강남구 삼성동 159".to_string(),
+        road_address: "서울 강남구 영동대로 513".to_string(),
+        longitude: Some(127.05902969025047),
+        latitude: Some(37.51207412593136),
+        url: "http://place.map.daum.net/26338954".to_string(),
+        distance: None,
+    };
+
+    let point = geo_types::Point::try_from(&place).unwrap();
+    assert_eq!(point.x(), 127.05902969025047);
+    assert_eq!(point.y(), 37.51207412593136);
+
+    let feature = place.to_geojson_feature();
+    assert_eq!(
+        feature.geometry.unwrap().value,
+        geojson::Value::Point(vec![127.05902969025047, 37.51207412593136])
+    );
+    let properties = feature.properties.unwrap();
+    assert_eq!(properties["name"], "카카오프렌즈 코엑스점");
+    assert_eq!(properties["category_group"], "CE7");
+
+    let no_coord = daummap::Place {
+        longitude: None,
+        ..place.clone()
+    };
+    assert!(geo_types::Point::try_from(&no_coord).is_err());
+    assert!(no_coord.to_geojson_feature().geometry.is_none());
+
+    let resp = daummap::KeywordResponse {
+        places: vec![place, no_coord],
+        total_count: 2,
+        pageable_count: 2,
+        is_end: true,
+    };
+    assert_eq!(resp.to_feature_collection().features.len(), 2);
+
+    let region = daummap::Region {
+        region_type: Some(daummap::RegionType::Legal),
+        address: "경기도 성남시 분당구 삼평동".to_string(),
+        province: "경기도".to_string(),
+        city: "성남시 분당구".to_string(),
+        town: "삼평동".to_string(),
+        neighborhood: String::new(),
+        code: Some(4113510900),
+        longitude: Some(127.1),
+        latitude: Some(37.4),
+        coord_system: daummap::CoordSystem::Wgs84,
+    };
+    assert_eq!(
+        region.to_geojson_feature().geometry.unwrap().value,
+        geojson::Value::Point(vec![127.1, 37.4])
+    );
+
+    // GeoJSON is always WGS84, whatever system Kakao answered in.
+    let (x, y) = daummap::proj::Projection::Wtm.from_wgs84(127.1, 37.4);
+    let wtm = daummap::Region {
+        longitude: Some(x),
+        latitude: Some(y),
+        coord_system: daummap::CoordSystem::Wtm,
+        ..region.clone()
+    };
+    let feature = wtm.to_geojson_feature();
+    match feature.geometry.unwrap().value {
+        geojson::Value::Point(point) => {
+            assert!((point[0] - 127.1).abs() < 1e-9);
+            assert!((point[1] - 37.4).abs() < 1e-9);
+        }
+        other => panic!("unexpected geometry: {:?}", other),
+    }
+    assert_eq!(feature.properties.unwrap()["coord_system"], "WTM");
+
+    let ktm = daummap::Region {
+        coord_system: daummap::CoordSystem::Ktm,
+        ..wtm
+    };
+    let feature = ktm.to_geojson_feature();
+    assert!(feature.geometry.is_none());
+    assert_eq!(feature.properties.unwrap()["coord_system"], "KTM");
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_cli() {
+    let mock = MockKakao::start().await;
+    let url = mock.url();
+
+    let output = tokio::task::spawn_blocking(move || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_daummap"))
+            .args([
+                "--base-url",
+                &url,
+                "--format",
+                "csv",
+                "category",
+                "PM9",
+                "--rect",
+                "127,37.5,127.1,37.6",
+            ])
+            .env("KAKAO_REST_API_KEY", "key")
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].path, "/search/category.json");
+    assert_eq!(
+        requests[0].query,
+        "category_group_code=PM9&page=1&size=15&sort=accuracy&rect=127%2C37.5%2C127.1%2C37.6"
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "id,name,category,phone,address,road_address,longitude,latitude,distance\n\
+         16618597,장생당약국,\"의료,건강 > 약국\",02-558-5476,서울 강남구 대치동 943-16,\
+         서울 강남구 테헤란로84길 17,127.05897078335246,37.506051888130386,\n"
+    );
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_cli_reverse_batch() {
+    let mock = MockKakao::start().await;
+    let url = mock.url();
+    let dir = std::env::temp_dir().join(format!("daummap-cli-reverse-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (points, bad_points) = (dir.join("points.ndjson"), dir.join("bad.ndjson"));
+    std::fs::write(
+        &points,
+        "{\"id\": \"a\", \"x\": 127.1, \"y\": 37.4}\n{\"id\": \"b\", \"x\": 127.2, \"y\": 37.5}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &bad_points,
+        "{\"id\": \"a\", \"x\": 127.1, \"y\": 37.4}\n{\"x\": 127.1,\n",
+    )
+    .unwrap();
+
+    let run = |format: &'static str, input: &std::path::Path| {
+        let (url, input) = (url.clone(), input.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            std::process::Command::new(env!("CARGO_BIN_EXE_daummap"))
+                .args(["--base-url", &url, "--format", format, "reverse-batch"])
+                .arg(input)
+                .env("KAKAO_REST_API_KEY", "key")
+                .output()
+                .unwrap()
+        })
+    };
+
+    let output = run("json", &points).await.unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let matches = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap();
+    assert_eq!(matches.as_array().map(Vec::len), Some(2));
+    assert_eq!(matches[1]["point"]["id"], "b");
+
+    // Rows matched before the unreadable line are written out.
+    let output = run("ndjson", &bad_points).await.unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.contains("\"id\":\"a\""));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Cannot parse the input at line 2"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_cli_negative_coords() {
+    let mock = MockKakao::start().await;
+    let url = mock.url();
+
+    let run = |args: &'static [&'static str]| {
+        let url = url.clone();
+        tokio::task::spawn_blocking(move || {
+            std::process::Command::new(env!("CARGO_BIN_EXE_daummap"))
+                .args(["--base-url", &url])
+                .args(args)
+                .env("KAKAO_REST_API_KEY", "key")
+                .output()
+                .unwrap()
+        })
+    };
+
+    let output = run(&[
+        "transcoord",
+        "160710.37",
+        "-4388.87",
+        "--input-coord",
+        "WTM",
+    ])
+    .await
+    .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(&["region", "-12.5", "-4388.87", "--input-coord", "WTM"])
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(&["reverse", "160710.37", "-4388.87", "--input-coord", "WTM"])
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(&["category", "PM9", "--rect", "-127,-37.5,127.1,37.6"])
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(&["keyword", "foo", "--x", "-127.0", "--y", "37.5"])
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(&["category", "PM9", "--x", "127.0", "--y", "-37.5"])
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 6);
+    assert_eq!(requests[0].path, "/geo/transcoord.json");
+    assert_eq!(requests[0].param("x"), Some("160710.37"));
+    assert_eq!(requests[0].param("y"), Some("-4388.87"));
+    assert_eq!(requests[0].param("input_coord"), Some("WTM"));
+    assert_eq!(requests[1].param("x"), Some("-12.5"));
+    assert_eq!(requests[1].param("y"), Some("-4388.87"));
+    assert_eq!(requests[2].param("y"), Some("-4388.87"));
+    assert_eq!(requests[3].param("rect"), Some("-127,-37.5,127.1,37.6"));
+    assert_eq!(requests[4].param("x"), Some("-127"));
+    assert_eq!(requests[4].param("y"), Some("37.5"));
+    assert_eq!(requests[5].param("x"), Some("127"));
+    assert_eq!(requests[5].param("y"), Some("-37.5"));
+}
+
+#[cfg(feature = "batch")]
+#[tokio::test]
+async fn test_batch() {
+    static ADDRESS_RESP: &str = r#"{
+  "meta": { "total_count": 1, "pageable_count": 1, "is_end": true },
+  "documents": [
+    {
+      "address_name": "서울 강남구 영동대로 513",
+      "y": "37.51207412593136",
+      "x": "127.05902969025047",
+      "address_type": "ROAD_ADDR",
+      "address": {
+        "address_name": "서울 강남구 삼성동 159",
+        "region_1depth_name": "서울",
+        "region_2depth_name": "강남구",
+        "region_3depth_name": "삼성동",
+        "region_3depth_h_name": "삼성1동",
+        "h_code": "1168058000",
+        "b_code": "1168010500",
+        "mountain_yn": "N",
+        "main_address_no": "159",
+        "sub_address_no": "",
+        "zip_code": "",
+        "x": "127.05902969025047",
+        "y": "37.51207412593136"
+      },
+      "road_address": {
+        "address_name": "서울 강남구 영동대로 513",
+        "region_1depth_name": "서울",
+        "region_2depth_name": "강남구",
+        "region_3depth_name": "삼성동",
+        "road_name": "영동대로",
+        "underground_yn": "N",
+        "main_building_no": "513",
+        "sub_building_no": "",
+        "building_name": "코엑스",
+        "zone_no": "06164",
+        "y": "37.51207412593136",
+        "x": "127.05902969025047"
+      }
+    }
+  ]
+}"#;
+    static KEYWORD_RESP: &str = r#"{
+  "meta": { "total_count": 1, "pageable_count": 1, "is_end": true },
+  "documents": [
+    {
+      "place_name": "코엑스",
+      "distance": "",
+      "place_url": "http://place.map.daum.net/7942972",
+      "category_name": "문화,예술 > 문화시설 > 전시관 > 컨벤션센터",
+      "address_name": "서울 강남구 삼성동 159",
+      "road_address_name": "서울 강남구 영동대로 513",
+      "id": "7942972",
+      "phone": "02-6000-0114",
+      "category_group_code": "CT1",
+      "category_group_name": "문화시설",
+      "x": "127.05902969025047",
+      "y": "37.51207412593136"
+    }
+  ]
+}"#;
+    static EMPTY_RESP: &str =
+        r#"{ "meta": { "total_count": 0, "pageable_count": 0, "is_end": true }, "documents": [] }"#;
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/address.json").respond(EMPTY_RESP);
+    mock.on("/search/keyword.json").respond(EMPTY_RESP);
+    mock.on("/search/address.json")
+        .param("query", "서울 강남구 영동대로 513")
+        .respond(ADDRESS_RESP);
+    mock.on("/search/keyword.json")
+        .param("query", "코엑스")
+        .respond(KEYWORD_RESP);
+    mock.on("/search/address.json")
+        .param("query", "없는 곳")
+        .status(StatusCode::BAD_GATEWAY)
+        .times(1)
+        .respond("Bad Gateway");
+
+    let dir = std::env::temp_dir().join(format!("daummap-batch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (input, output) = (dir.join("input.csv"), dir.join("output.csv"));
+    std::fs::write(
+        &input,
+        "id,address\n1,서울 강남구 영동대로 513\n2,코엑스\n3,없는 곳\n4,\n",
+    )
+    .unwrap();
+
+    let client = mock.client("key");
+    let mut batch = daummap::batch::Batch::new(&client, "address");
+    batch.concurrency(1).checkpoint_interval(1);
+
+    let err = batch.run(&input, &output).await.unwrap_err();
+    assert!(matches!(err, daummap::batch::BatchError::Api(_)));
+    // One address search for the first row, an address and a keyword search for the second and
+    // the failed one for the third.
+    assert_eq!(mock.take_requests().len(), 4);
+    assert!(dir.join("output.csv.checkpoint").exists());
+
+    let stats = batch.run(&input, &output).await.unwrap();
+
+    assert_eq!(mock.take_requests().len(), 2);
+    assert_eq!(stats.resumed, 2);
+    assert_eq!(stats.rows, 2);
+    assert_eq!(stats.unmatched, 2);
+    assert!(!dir.join("output.csv.checkpoint").exists());
+
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        "id,address,road_address,land_lot_address,longitude,latitude,b_code,match_type,confidence\n\
+         1,서울 강남구 영동대로 513,서울 강남구 영동대로 513,서울 강남구 삼성동 159,\
+         127.05902969025047,37.51207412593136,1168010500,ROAD_ADDR,1.00\n\
+         2,코엑스,서울 강남구 영동대로 513,서울 강남구 삼성동 159,\
+         127.05902969025047,37.51207412593136,,KEYWORD,0.30\n\
+         3,없는 곳,,,,,,,\n\
+         4,,,,,,,,\n"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "batch")]
+#[tokio::test]
+async fn test_reverse_batch() {
+    use {
+        daummap::batch::reverse::{read_points, ReverseBatch, ReversePoint},
+        futures::{StreamExt, TryStreamExt},
+    };
+
+    static REGION_RESP: &str = r#"{
+  "meta": { "total_count": 2 },
+  "documents": [
+    {
+      "region_type": "B",
+      "address_name": "경기도 성남시 분당구 삼평동",
+      "region_1depth_name": "경기도",
+      "region_2depth_name": "성남시 분당구",
+      "region_3depth_name": "삼평동",
+      "region_4depth_name": "",
+      "code": "4113510900",
+      "x": 127.10459896729914,
+      "y": 37.40269721785548
+    },
+    {
+      "region_type": "H",
+      "address_name": "경기도 성남시 분당구 삼평동",
+      "region_1depth_name": "경기도",
+      "region_2depth_name": "성남시 분당구",
+      "region_3depth_name": "삼평동",
+      "region_4depth_name": "",
+      "code": "4113565500",
+      "x": 127.1163593869371,
+      "y": 37.40612091848614
+    }
+  ]
+}"#;
+    static ADDRESS_RESP: &str = r#"{
+  "meta": { "total_count": 1 },
+  "documents": [
+    {
+      "road_address": {
+        "address_name": "경기도 성남시 분당구 판교역로 235",
+        "region_1depth_name": "경기",
+        "region_2depth_name": "성남시 분당구",
+        "region_3depth_name": "삼평동",
+        "road_name": "판교역로",
+        "underground_yn": "N",
+        "main_building_no": "235",
+        "sub_building_no": "",
+        "building_name": "에이치스퀘어 엔동",
+        "zone_no": "13494"
+      },
+      "address": {
+        "address_name": "경기 성남시 분당구 삼평동 681",
+        "region_1depth_name": "경기",
+        "region_2depth_name": "성남시 분당구",
+        "region_3depth_name": "삼평동",
+        "mountain_yn": "N",
+        "main_address_no": "681",
+        "sub_address_no": "",
+        "zip_code": ""
+      }
+    }
+  ]
+}"#;
+
+    let mock = MockKakao::start().await;
+    mock.on("/geo/coord2regioncode.json").respond(REGION_RESP);
+    mock.on("/geo/coord2address.json").respond(ADDRESS_RESP);
+
+    let dir = std::env::temp_dir().join(format!("daummap-reverse-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (ndjson, geojson, csv) = (
+        dir.join("points.ndjson"),
+        dir.join("points.geojson"),
+        dir.join("points.csv"),
+    );
+    // The first two points are a few meters apart.
+    std::fs::write(
+        &ndjson,
+        "{\"id\": \"a\", \"x\": 127.10860, \"y\": 37.40206}\n\
+         {\"id\": 2, \"lon\": 127.10862, \"lat\": 37.40207}\n\
+         \n\
+         {\"type\": \"Feature\", \"id\": \"c\", \"geometry\": \
+         {\"type\": \"Point\", \"coordinates\": [127.11500, 37.40500]}, \"properties\": {}}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &geojson,
+        r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [127.1, 37.4]},
+             "properties": {"id": "p"}}
+        ]}"#,
+    )
+    .unwrap();
+    std::fs::write(&csv, "id,longitude,latitude\nq,127.1,37.4\nr,127.1,\n").unwrap();
+
+    let read = |path: &std::path::Path| {
+        read_points(path)
+            .unwrap()
+            .collect::<Result<Vec<_>, daummap::batch::BatchError>>()
+    };
+    assert_eq!(
+        read(&geojson).unwrap(),
+        vec![ReversePoint {
+            id: Some("p".to_string()),
+            longitude: 127.1,
+            latitude: 37.4,
+        }]
+    );
+    assert!(matches!(
+        read(&csv).unwrap_err(),
+        daummap::batch::BatchError::InvalidPoint(3)
+    ));
+
+    let (bad_ndjson, bad_geojson, not_collection) = (
+        dir.join("bad.ndjson"),
+        dir.join("bad.geojson"),
+        dir.join("point.geojson"),
+    );
+    std::fs::write(&bad_ndjson, "{\"x\": 127.1, \"y\": 37.4}\n{\"x\": 127.1,\n").unwrap();
+    std::fs::write(
+        &bad_geojson,
+        "{\"type\": \"FeatureCollection\",\n\"features\": [}",
+    )
+    .unwrap();
+    std::fs::write(
+        &not_collection,
+        r#"{"type": "Point", "coordinates": [127.1, 37.4]}"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        read(&bad_ndjson).unwrap_err(),
+        daummap::batch::BatchError::InvalidInput { line: 2, .. }
+    ));
+    assert!(matches!(
+        read_points(&bad_geojson).unwrap_err(),
+        daummap::batch::BatchError::InvalidInput { line: 2, .. }
+    ));
+    let err = read_points(&not_collection).unwrap_err();
+    assert!(matches!(err, daummap::batch::BatchError::MissingFeatures));
+    assert_eq!(err.to_string(), "The GeoJSON input has no features");
+
+    let points = read(&ndjson).unwrap();
+    assert_eq!(points.len(), 3);
+    assert_eq!(points[1].id.as_deref(), Some("2"));
+    assert_eq!(points[2].id.as_deref(), Some("c"));
+
+    let client = mock.client("key");
+
+    let matches = ReverseBatch::new(&client)
+        .stream(points.clone())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    // Two requests for each distinct coordinate.
+    assert_eq!(mock.take_requests().len(), 6);
+    assert_eq!(matches.len(), 3);
+
+    let matches = ReverseBatch::new(&client)
+        .snap_grid(50.0)
+        .stream(points)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    assert_eq!(mock.take_requests().len(), 4);
+    assert_eq!(matches.len(), 3);
+
+    assert_eq!(matches[1].point.longitude, 127.10862);
+    assert_eq!(
+        matches[0].h_region.as_ref().and_then(|region| region.code),
+        Some(4113565500)
+    );
+    assert_eq!(
+        matches[1].b_region.as_ref().and_then(|region| region.code),
+        Some(4113510900)
+    );
+    assert_eq!(
+        matches[2].road.as_ref().map(|road| road.address.as_str()),
+        Some("경기도 성남시 분당구 판교역로 235")
+    );
+    assert_eq!(
+        matches[2]
+            .land_lot
+            .as_ref()
+            .map(|land_lot| land_lot.address.as_str()),
+        Some("경기 성남시 분당구 삼평동 681")
+    );
+
+    // Points read from a file are matched until the first one that cannot be read.
+    let results = ReverseBatch::new(&client)
+        .try_stream(read_points(&bad_ndjson).unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().point.longitude, 127.1);
+    assert!(matches!(
+        results[1],
+        Err(daummap::batch::BatchError::InvalidInput { line: 2, .. })
+    ));
+    assert_eq!(mock.take_requests().len(), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_cache() {
+    static KEYWORD_RESP: &str = r#"{
+  "meta": { "total_count": 0, "pageable_count": 0, "is_end": true },
+  "documents": []
+}"#;
+    static REGION_RESP: &str = r#"{ "meta": { "total_count": 0 }, "documents": [] }"#;
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/keyword.json").respond(KEYWORD_RESP);
+    mock.on("/geo/coord2regioncode.json").respond(REGION_RESP);
+    mock.on("/search/address.json")
+        .status(StatusCode::BAD_GATEWAY)
+        .respond("Bad Gateway");
+
+    let cache = daummap::ResponseCache::new(2);
+    let client = mock.builder("key").cache(cache.clone()).build().unwrap();
+
+    client.keyword("강남역").get().await.unwrap();
+    // The same parameters, set in another order.
+    client
+        .keyword("강남역")
+        .size(15)
+        .page(1)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(mock.requests().len(), 1);
+
+    client.keyword("강남역").no_cache().get().await.unwrap();
+    client.keyword("강남역").page(2).get().await.unwrap();
+    assert_eq!(mock.requests().len(), 3);
+
+    client.coord(127.1, 37.4).get_region().await.unwrap();
+    client.coord(127.1, 37.4).get_region().await.unwrap();
+    assert_eq!(mock.requests().len(), 4);
+    // Evicted as the least recently used.
+    client.keyword("강남역").get().await.unwrap();
+    assert_eq!(mock.requests().len(), 5);
+
+    // Errors are not cached.
+    assert!(client.address("강남역").get().await.is_err());
+    assert!(client.address("강남역").get().await.is_err());
+    assert_eq!(mock.requests().len(), 7);
+
+    assert_eq!(
+        cache.stats(),
+        daummap::CacheStats {
+            hits: 2,
+            misses: 6,
+            entries: 2,
+        }
+    );
+
+    let client = mock
+        .builder("key")
+        .cache(
+            daummap::ResponseCache::new(10)
+                .ttl(Duration::from_millis(100))
+                .clone(),
+        )
+        .build()
+        .unwrap();
+    mock.take_requests();
+
+    client.keyword("강남역").get().await.unwrap();
+    client.keyword("강남역").get().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    client.keyword("강남역").get().await.unwrap();
+
+    assert_eq!(mock.requests().len(), 2);
+    let stats = client.cache().unwrap().stats();
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+}
+
+#[tokio::test]
+async fn test_file_cache() {
+    use {
+        daummap::cache::{CacheKey, CacheStore, CachedResponse, FileStore, ResponseCache},
+        std::time::SystemTime,
+    };
+
+    static KEYWORD_RESP: &str = r#"{
+  "meta": { "total_count": 0, "pageable_count": 0, "is_end": true },
+  "documents": []
+}"#;
+    static CACHED_RESP: &str = r#"{
+  "meta": { "total_count": 1, "pageable_count": 1, "is_end": true },
+  "documents": [
+    {
+      "address_name": "서울 강남구 역삼동 858",
+      "address_type": "REGION_ADDR",
+      "x": "127.027610964151",
+      "y": "37.4979502421797",
+      "address": null,
+      "road_address": null
+    }
+  ]
+}"#;
+
+    let mock = MockKakao::start().await;
+    mock.on("/search/keyword.json").respond(KEYWORD_RESP);
+    mock.on("/search/address.json")
+        .status(StatusCode::BAD_GATEWAY)
+        .respond("Bad Gateway");
+
+    let dir = std::env::temp_dir().join(format!("daummap-cache-{}", std::process::id()));
+    let client = |cache: &ResponseCache| mock.builder("key").cache(cache.clone()).build().unwrap();
+
+    let cache = ResponseCache::with_store(FileStore::new(&dir).unwrap());
+    client(&cache).keyword("강남역").get().await.unwrap();
+    // Read back by another store on the same directory.
+    let cache = ResponseCache::with_store(FileStore::new(&dir).unwrap());
+    client(&cache).keyword("강남역").get().await.unwrap();
+    assert_eq!(mock.take_requests().len(), 1);
+    assert_eq!(cache.stats().hits, 1);
+    assert_eq!(cache.stats().entries, 1);
+
+    // The same search sent elsewhere is not answered with the response of the mock.
+    let other = MockKakao::start().await;
+    other.on("/search/keyword.json").respond(KEYWORD_RESP);
+    let client_elsewhere = other.builder("key").cache(cache.clone()).build().unwrap();
+    client_elsewhere.keyword("강남역").get().await.unwrap();
+    assert_eq!(other.requests().len(), 1);
+    assert_eq!(cache.stats().entries, 2);
+    cache.clear().unwrap();
+    client(&cache).keyword("강남역").get().await.unwrap();
+    assert_eq!(mock.take_requests().len(), 1);
+
+    // An address search cached a year ago; Kakao fails on address searches.
+    let store = FileStore::new(&dir).unwrap();
+    let key = CacheKey::new(
+        &mock.url(),
+        "/search/address.json",
+        &[
+            ("query", "강남역".to_string()),
+            ("page", "1".to_string()),
+            ("size", "15".to_string()),
+        ],
+    );
+    store
+        .put(
+            &key,
+            &CachedResponse {
+                body: CACHED_RESP.to_string(),
+                stored_at: SystemTime::now() - Duration::from_secs(365 * 24 * 60 * 60),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        store.get(&key).unwrap().map(|cached| cached.body),
+        Some(CACHED_RESP.to_string())
+    );
+
+    let mut cache = ResponseCache::with_store(FileStore::new(&dir).unwrap());
+    cache.refresh_if_older_than(Duration::from_secs(24 * 60 * 60));
+    let resp = client(&cache).address("강남역").get().await.unwrap();
+    assert_eq!(mock.take_requests().len(), 1);
+    assert_eq!(
+        resp.addresses[0].address.as_deref(),
+        Some("서울 강남구 역삼동 858")
+    );
+    // Errors that are not transient are not hidden behind the cached response.
+    mock.on("/search/address.json")
+        .status(StatusCode::UNAUTHORIZED)
+        .respond(r#"{"errorType":"AccessDeniedError","message":"cannot find appKey"}"#);
+    match client(&cache).address("강남역").get().await {
+        Err(daummap::Error::Api(e)) => assert!(e.is_invalid_key()),
+        other => panic!("unexpected result: {:?}", other.map(|resp| resp.addresses)),
+    }
+    mock.on("/search/address.json")
+        .status(StatusCode::BAD_GATEWAY)
+        .respond("Bad Gateway");
+    assert_eq!(mock.take_requests().len(), 1);
+
+    let mut cache = ResponseCache::with_store(FileStore::new(&dir).unwrap());
+    cache.offline(true);
+    let resp = client(&cache).address("강남역").get().await.unwrap();
+    assert_eq!(resp.addresses.len(), 1);
+    match client(&cache).keyword("역삼역").get().await {
+        Err(daummap::Error::CacheMiss { path }) => assert_eq!(path, "search/keyword.json"),
+        other => panic!("unexpected result: {:?}", other.map(|resp| resp.places)),
+    }
+    assert_eq!(mock.take_requests().len(), 0);
+
+    let mut cache = ResponseCache::with_store(FileStore::new(&dir).unwrap());
+    cache.ttl(Duration::from_secs(30 * 24 * 60 * 60));
+    assert!(client(&cache).address("강남역").get().await.is_err());
+    assert_eq!(mock.take_requests().len(), 1);
+    assert_eq!(store.get(&key).unwrap(), None);
+    assert_eq!(cache.stats().entries, 1);
+
+    cache.clear().unwrap();
+    assert_eq!(cache.stats().entries, 0);
+
+    // Concurrent writers of one entry leave a whole entry and no temporary file behind.
+    let writers = (0..8)
+        .map(|i| {
+            let (store, key) = (store.clone(), key.clone());
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    let cached = CachedResponse {
+                        body: format!("{}{}", i, CACHED_RESP),
+                        stored_at: SystemTime::now(),
+                    };
+                    store.put(&key, &cached).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    let body = store.get(&key).unwrap().unwrap().body;
+    assert!(body.ends_with(CACHED_RESP));
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_mock_record_replay() {
+    let dir = std::env::temp_dir().join(format!("daummap-fixtures-{}", std::process::id()));
+
+    let upstream = MockKakao::start().await;
+    let recorder = MockKakao::record_from(&dir, &upstream.url()).await;
+    let recorded = recorder
+        .client("key")
+        .keyword("카카오프렌즈")
+        .get()
+        .await
+        .unwrap();
+    upstream.assert_received("/search/keyword.json", &[("query", "카카오프렌즈")]);
+    assert_eq!(upstream.requests()[0].app_key.as_deref(), Some("key"));
+
+    let replay = MockKakao::replay(&dir).await;
+    let client = replay.client("key");
+    let replayed = client.keyword("카카오프렌즈").get().await.unwrap();
+    assert_eq!(replayed.places[0].id, recorded.places[0].id);
+    assert_eq!(replayed.total_count, recorded.total_count);
+    assert_eq!(upstream.requests().len(), 1);
+
+    let err = client.keyword("카카오").get().await.unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+
+    replay.expect_app_key("another key");
+    match client.keyword("카카오프렌즈").get().await {
+        Err(daummap::Error::Api(e)) => assert!(e.is_invalid_key()),
+        other => panic!("unexpected result: {:?}", other.map(|resp| resp.places)),
+    }
+    assert_eq!(replay.requests().len(), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mock = runtime.block_on(MockKakao::start());
+    mock.expect_app_key("key");
+
+    let client = daummap::blocking::KakaoClient::from(mock.client("key"));
+
+    let resp = client
+        .address("전북 삼성동 100")
+        .page(2)
+        .size(5)
+        .get()
+        .unwrap();
+    assert_eq!(resp.total_count, 4);
+    assert_eq!(resp.addresses.len(), 1);
+
+    let resp = client
+        .keyword("카카오프렌즈")
+        .coord(127.06283102249932, 37.514322572335935)
+        .radius(20000)
+        .sort(daummap::Sort::Distance)
+        .get()
+        .unwrap();
+    assert_eq!(resp.total_count, 14);
+
+    let resp = client
+        .category_rect(
+            daummap::CategoryGroup::Pharmacy,
+            127.0561466,
+            37.5058277,
+            127.0602340,
+            37.5142554,
+        )
+        .get()
+        .unwrap();
+    assert_eq!(resp.total_count, 11);
+
+    let regions = client.coord(127.1, 37.4).get_region().unwrap();
+    assert_eq!(regions.len(), 2);
+    let addresses = client.coord(127.1, 37.4).get_address().unwrap();
+    assert_eq!(addresses.len(), 1);
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 5);
+    assert_eq!(
+        requests[0].query,
+        "query=%EC%A0%84%EB%B6%81+%EC%82%BC%EC%84%B1%EB%8F%99+100&page=2&size=5"
+    );
+    mock.assert_received(
+        "/search/keyword.json",
+        &[("radius", "20000"), ("sort", "distance")],
+    );
+    mock.assert_received("/search/category.json", &[("category_group_code", "PM9")]);
+
+    let err = daummap::blocking::KakaoClient::from(mock.client("wrong"))
+        .keyword("카카오프렌즈")
+        .get()
+        .unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+
+    // Standalone requests, as with the async API.
+    mock.take_requests();
+    let url = mock.url();
+    let resp = daummap::blocking::AddressRequest::new("key", "전북 삼성동 100")
+        .base_url(&url)
+        .get()
+        .unwrap();
+    assert_eq!(resp.total_count, 4);
+    let resp = daummap::blocking::KeywordRequest::new("key", "카카오프렌즈")
+        .base_url(&url)
+        .get()
+        .unwrap();
+    assert_eq!(resp.total_count, 14);
+    let resp = daummap::blocking::CategoryRequest::circle(
+        "key",
+        daummap::CategoryGroup::Pharmacy,
+        127.06,
+        37.51,
+        2000,
+    )
+    .base_url(&url)
+    .get()
+    .unwrap();
+    assert_eq!(resp.total_count, 11);
+    daummap::blocking::CategoryRequest::rect(
+        "key",
+        daummap::CategoryGroup::Pharmacy,
+        127.0561466,
+        37.5058277,
+        127.0602340,
+        37.5142554,
+    )
+    .base_url(&url)
+    .get()
+    .unwrap();
+    let regions = daummap::blocking::CoordRequest::new("key", 127.1, 37.4)
+        .base_url(&url)
+        .get_region()
+        .unwrap();
+    assert_eq!(regions.len(), 2);
+    let coords = daummap::blocking::TransCoordRequest::new("key", 160710.37, -4388.87)
+        .base_url(&url)
+        .input_coord(daummap::CoordSystem::Wtm)
+        .get()
+        .unwrap();
+    assert_eq!(coords.len(), 1);
+    let paths = mock
+        .take_requests()
+        .into_iter()
+        .map(|req| req.path)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "/search/address.json",
+            "/search/keyword.json",
+            "/search/category.json",
+            "/search/category.json",
+            "/geo/coord2regioncode.json",
+            "/geo/transcoord.json",
+        ]
+    );
+}